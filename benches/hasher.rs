@@ -0,0 +1,42 @@
+//! Times insertions and lookups of 1M u64 keys in SlickHash with the default FastMix block
+//! hasher against the same table hashing with SipHash: `cargo bench --bench hasher`
+
+use slick_hash::hash_table::HashTableBase;
+use slick_hash::{BuildFastMix, SlickHash};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{BuildHasher, BuildHasherDefault};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const KEYS: u64 = 1_000_000;
+const RUNS: usize = 5;
+
+// Best of several runs of filling a table and looking every key up again
+fn time_table<S: BuildHasher + Clone + Default>(keys: &[u64]) -> (Duration, Duration) {
+    let mut best = (Duration::MAX, Duration::MAX);
+    for _ in 0..RUNS {
+        let capacity = (keys.len() * 11 / 10).div_ceil(10) * 10;
+        let mut table: SlickHash<u64, u64, S> = SlickHash::with_capacity_and_hasher(capacity, S::default());
+        let start = Instant::now();
+        for &key in keys {
+            let _ = table.try_insert((key, key));
+        }
+        let insert = start.elapsed();
+        let start = Instant::now();
+        let found = keys.iter().filter(|&key| black_box(table.get(key)).is_some()).count();
+        let lookup = start.elapsed();
+        assert_eq!(found, keys.len());
+        best = (best.0.min(insert), best.1.min(lookup));
+    }
+    best
+}
+
+fn main() {
+    let keys: Vec<u64> = (0..KEYS).map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15)).collect();
+    for (name, (insert, lookup)) in [
+        ("FastMix", time_table::<BuildFastMix>(&keys)),
+        ("SipHash", time_table::<BuildHasherDefault<DefaultHasher>>(&keys)),
+    ] {
+        println!("{name}: insert {insert:?}, lookup {lookup:?}");
+    }
+}
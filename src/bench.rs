@@ -0,0 +1,48 @@
+use crate::hash_table::{HashTableBase, HashTableBulk};
+use crate::SlickHash;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::time::{Duration, Instant};
+
+/// Outcome of `SlickHash::bench_build_vs_hashmap`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuildComparison {
+    pub slick_duration: Duration,
+    pub slick_len: usize,
+    pub hashmap_duration: Duration,
+    pub hashmap_len: usize,
+}
+
+impl BuildComparison {
+    /// How many times longer the SlickHash took to build, below 1 if it was faster
+    pub fn slowdown(&self) -> f64 {
+        self.slick_duration.as_secs_f64() / self.hashmap_duration.as_secs_f64()
+    }
+}
+
+impl<Key, Value, S> SlickHash<Key, Value, S>
+where
+    Key: Clone + Eq + PartialEq + Hash + Default,
+    Value: Clone + Default,
+    S: BuildHasher + Clone + Default,
+{
+    /// Times building a SlickHash and a std HashMap from the same pairs. A single run, so
+    /// repeat it for anything more than a rough impression.
+    pub fn bench_build_vs_hashmap(pairs: &[(Key, Value)]) -> BuildComparison {
+        // Both builds include the allocation and keep the first value of a duplicate key
+        let start = Instant::now();
+        let mut slick: Self = HashTableBase::with_capacity(pairs.len());
+        slick.bulk_insert(pairs);
+        let slick_duration = start.elapsed();
+
+        let start = Instant::now();
+        let mut hashmap = HashMap::with_capacity(pairs.len());
+        for (key, value) in pairs {
+            hashmap.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+        let hashmap_duration = start.elapsed();
+
+        let (slick_len, hashmap_len) = (HashTableBase::len(&slick), hashmap.len());
+        BuildComparison { slick_duration, slick_len, hashmap_duration, hashmap_len }
+    }
+}
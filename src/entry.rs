@@ -0,0 +1,154 @@
+use crate::{HashedKey, Location, SlickHash};
+use crate::BuildFastMix;
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+
+/// A view into a single entry of a `SlickHash`, which is either occupied or vacant
+pub enum Entry<'t, Key, Value, S = BuildFastMix> {
+    Occupied(OccupiedEntry<'t, Key, Value, S>),
+    Vacant(VacantEntry<'t, Key, Value, S>),
+}
+
+/// An entry whose key is present, either in its main table block or in the backyard
+pub struct OccupiedEntry<'t, Key, Value, S = BuildFastMix> {
+    table: &'t mut SlickHash<Key, Value, S>,
+    key: Key,
+    // Resolved when the entry is created; the exclusive borrow of the table keeps it valid
+    location: Location,
+}
+
+pub struct VacantEntry<'t, Key, Value, S = BuildFastMix> {
+    table: &'t mut SlickHash<Key, Value, S>,
+    key: Key,
+    // Block and threshold of the key, resolved with the lookup; inserting neither hashes the
+    // key again nor searches for it
+    hashed: HashedKey,
+}
+
+impl<Key, Value, S> SlickHash<Key, Value, S>
+where
+    Key: Clone + Eq + PartialEq + Hash + Default,
+    Value: Clone + Default,
+    S: BuildHasher + Clone,
+{
+    pub fn entry(&mut self, key: Key) -> Entry<'_, Key, Value, S> {
+        let hashed = self.hash_key(&key);
+        match self.locate_hashed(&key, hashed) {
+            Some(location) => Entry::Occupied(OccupiedEntry { table: self, key, location }),
+            None => Entry::Vacant(VacantEntry { table: self, key, hashed }),
+        }
+    }
+
+    /// Like `entry(key).or_insert_with(make_value)`, but looks the key up by reference, so
+    /// that the owned key is only built by `make_key` if it is absent. Neither closure runs
+    /// for a present key.
+    pub fn get_or_insert_ref<Q, F>(&mut self, key: &Q, make_key: impl FnOnce() -> Key, make_value: F) -> &mut Value
+    where
+        Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: FnOnce() -> Value,
+    {
+        let hashed = self.hash_key(key);
+        match self.locate_hashed(key, hashed) {
+            Some(Location::MainTable(index)) => &mut self.main_table[index].1,
+            Some(Location::Backyard) => self.backyard.get_mut(key).expect("entry located in the backyard"),
+            None => {
+                let owned_key = make_key();
+                debug_assert!(owned_key.borrow() == key, "make_key built a different key");
+                self.insert_absent(owned_key, hashed, make_value()).into_mut()
+            }
+        }
+    }
+}
+
+impl<'t, Key, Value, S> Entry<'t, Key, Value, S>
+where
+    Key: Clone + Eq + PartialEq + Hash + Default,
+    Value: Clone + Default,
+    S: BuildHasher + Clone,
+{
+    pub fn key(&self) -> &Key {
+        match self {
+            Entry::Occupied(occupied) => occupied.key(),
+            Entry::Vacant(vacant) => vacant.key(),
+        }
+    }
+
+    /// Applies `f` to the value if the entry is occupied, wherever the value lives
+    pub fn and_modify<F: FnOnce(&mut Value)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(occupied) = &mut self {
+            f(occupied.get_mut());
+        }
+        self
+    }
+
+    pub fn or_insert(self, default: Value) -> &'t mut Value {
+        match self {
+            Entry::Occupied(occupied) => occupied.into_mut(),
+            Entry::Vacant(vacant) => vacant.insert(default),
+        }
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> Value>(self, default: F) -> &'t mut Value {
+        match self {
+            Entry::Occupied(occupied) => occupied.into_mut(),
+            Entry::Vacant(vacant) => vacant.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> &'t mut Value {
+        self.or_insert_with(Value::default)
+    }
+}
+
+impl<'t, Key, Value, S> OccupiedEntry<'t, Key, Value, S>
+where
+    Key: Clone + Eq + PartialEq + Hash + Default,
+    Value: Clone + Default,
+    S: BuildHasher + Clone,
+{
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+
+    pub fn get(&self) -> &Value {
+        match self.location {
+            Location::MainTable(index) => &self.table.main_table[index].1,
+            Location::Backyard => self.table.backyard.get(&self.key).expect("entry located in the backyard"),
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut Value {
+        match self.location {
+            Location::MainTable(index) => &mut self.table.main_table[index].1,
+            Location::Backyard => self.table.backyard.get_mut(&self.key).expect("entry located in the backyard"),
+        }
+    }
+
+    pub fn into_mut(self) -> &'t mut Value {
+        match self.location {
+            Location::MainTable(index) => &mut self.table.main_table[index].1,
+            Location::Backyard => self.table.backyard.get_mut(&self.key).expect("entry located in the backyard"),
+        }
+    }
+
+    /// Replaces the value, returning the old one
+    pub fn insert(&mut self, value: Value) -> Value {
+        std::mem::replace(self.get_mut(), value)
+    }
+}
+
+impl<'t, Key, Value, S> VacantEntry<'t, Key, Value, S>
+where
+    Key: Clone + Eq + PartialEq + Hash + Default,
+    Value: Clone + Default,
+    S: BuildHasher + Clone,
+{
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+
+    pub fn insert(self, value: Value) -> &'t mut Value {
+        self.table.insert_absent(self.key, self.hashed, value).into_mut()
+    }
+}
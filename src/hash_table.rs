@@ -23,28 +23,50 @@ impl Capacity for usize {
         self
     }
 }
+/// A capacity enlarged by the factor `1 + epsilon`, rounded up and never below the base capacity
 #[derive(Copy, Clone)]
 pub struct WithMargin(pub usize, pub f64);
 
+impl WithMargin {
+    /// `Capacity::capacity` for use in const contexts, which trait methods can't be called in
+    pub const fn const_capacity(self) -> usize {
+        let WithMargin(capacity, epsilon) = self;
+        let enlarged = ((1.0 + epsilon) * capacity as f64).ceil() as usize;
+        if enlarged > capacity { enlarged } else { capacity }
+    }
+}
+
 impl Capacity for WithMargin {
     #[inline(always)]
     fn capacity(self) -> usize {
-        let WithMargin(capacity, epsilon) = self;
-        ((1.0 + epsilon) * capacity as f64) as usize
+        self.const_capacity()
     }
 }
 
+#[must_use]
 pub enum Insertion<'t, V> {
     Inserted(&'t mut V),
     Occupied(&'t mut V),
 }
-impl<V> Insertion<'_, V> {
+impl<'t, V> Insertion<'t, V> {
     pub fn is_inserted(&self) -> bool {
         match self {
             Insertion::Inserted(_) => true,
             Insertion::Occupied(_) => false,
         }
     }
+
+    pub fn is_occupied(&self) -> bool {
+        !self.is_inserted()
+    }
+
+    /// The value in the table, borrowed for as long as the table itself
+    pub fn into_mut(self) -> &'t mut V {
+        match self {
+            Insertion::Inserted(v) => v,
+            Insertion::Occupied(v) => v,
+        }
+    }
 }
 
 impl<'t, V> AsMut<V> for Insertion<'t, V> {
@@ -66,22 +88,92 @@ impl<'t, V> AsRef<V> for Insertion<'t, V> {
 }
 
 pub trait HashTableBase<Key, Value> {
+    /// Capacity `new` creates the table with, for generic code without a size in mind. Like
+    /// `HashMap::new`, the default of 0 leaves allocating to the first insertion.
+    const DEFAULT_CAPACITY: usize = 0;
     fn with_capacity(capacity: impl Capacity) -> Self;
-    fn try_insert(&mut self, key_value_pair: (Key, Value)) -> Insertion<Value>;
+    fn new() -> Self
+    where
+        Self: Sized,
+    {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+    fn try_insert(&mut self, key_value_pair: (Key, Value)) -> Insertion<'_, Value>;
+    /// Inserts or overwrites, handing back the previous value instead of a borrow of the table
+    fn insert_owned(&mut self, key: Key, value: Value) -> Option<Value>
+    where
+        Value: Clone,
+    {
+        match self.try_insert((key, value.clone())) {
+            Insertion::Inserted(_) => None,
+            Insertion::Occupied(previous) => Some(std::mem::replace(previous, value)),
+        }
+    }
     fn get(&self, key: &Key) -> Option<&Value>;
+    /// Mutable access to a present key's value. Tables that only hand out shared references
+    /// can leave it out, like `len`.
+    #[allow(unused_variables)]
+    fn get_mut(&mut self, key: &Key) -> Option<&mut Value> {
+        unimplemented!("get_mut not implemented")
+    }
     fn contains(&self, key: &Key) -> bool {
         self.get(key).is_some()
     }
+    /// Whether inserting the key now would report `Insertion::Occupied`, leaving the present
+    /// value in place, instead of adding a new entry
+    fn would_overwrite(&self, key: &Key) -> bool {
+        self.contains(key)
+    }
+    /// Number of the keys that are present
+    fn bulk_contains(&self, keys: &[Key]) -> usize {
+        keys.iter().filter(|key| self.contains(key)).count()
+    }
+    /// Looks up every key, returning the values in the order of the keys
+    fn get_batch<'a>(&'a self, keys: &[Key]) -> Vec<Option<&'a Value>> {
+        keys.iter().map(|key| self.get(key)).collect()
+    }
+    /// Number of entries. Tables that don't track it can leave it out, as long as nothing
+    /// calls it or `is_empty`.
+    fn len(&self) -> usize {
+        unimplemented!("len not implemented")
+    }
+    /// Prepares for `additional` more entries; tables without a notion of capacity ignore it
+    #[allow(unused_variables)]
+    fn reserve(&mut self, additional: usize) {}
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 pub trait HashTableRemove<Key, Value> {
     fn remove_entry(&mut self, key: &Key) -> Option<(Key, Value)>;
+    fn remove(&mut self, key: &Key) -> Option<Value> {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+    /// Removes all given keys and returns how many were present
+    fn remove_all(&mut self, keys: &[Key]) -> usize {
+        keys.iter().filter(|key| self.remove_entry(key).is_some()).count()
+    }
 }
 
 pub trait HashTableBulk<Key, Value> {
     fn bulk_insert(&mut self, key_value_pairs: &[(Key, Value)]);
 }
 
+/// Uniform benchmark entry points: construction and bulk insertion via the supertraits, plus a
+/// batch lookup to time
+pub trait BenchTable<Key, Value>: HashTableBase<Key, Value> + HashTableBulk<Key, Value> {
+    /// Looks up every key and returns the number of hits
+    fn lookup_all(&self, keys: &[Key]) -> usize {
+        self.bulk_contains(keys)
+    }
+}
+
+impl<Key, Value, T> BenchTable<Key, Value> for T where
+    T: HashTableBase<Key, Value> + HashTableBulk<Key, Value>
+{
+}
+
 pub trait DefaultHashTableBuild {}
 
 impl<Key, Value, T> HashTableBulk<Key, Value> for T
@@ -92,7 +184,7 @@ where
 {
     fn bulk_insert(&mut self, key_value_pairs: &[(Key, Value)]) {
         for pair in key_value_pairs.iter().copied() {
-            self.try_insert(pair);
+            let _ = self.try_insert(pair);
         }
     }
 }
@@ -119,6 +211,18 @@ pub trait Named {
     fn name() -> String;
 }
 
+/// Instance-level counterpart of `Named::name`, callable on a `&dyn TypeName` where only a value
+/// of the table is at hand
+pub trait TypeName {
+    fn type_name(&self) -> String;
+}
+
+impl<T: Named> TypeName for T {
+    fn type_name(&self) -> String {
+        T::name()
+    }
+}
+
 pub mod std_map {
     use std::collections::{hash_map::Entry, HashMap};
 
@@ -135,7 +239,7 @@ pub mod std_map {
             HashMap::with_capacity(capacity.capacity())
         }
 
-        fn try_insert(&mut self, key_value_pair: (u64, u64)) -> Insertion<u64> {
+        fn try_insert(&mut self, key_value_pair: (u64, u64)) -> Insertion<'_, u64> {
             let (key, value) = key_value_pair;
             match self.entry(key) {
                 Entry::Occupied(occ) => Insertion::Occupied(occ.into_mut()),
@@ -143,13 +247,33 @@ pub mod std_map {
             }
         }
 
+        fn insert_owned(&mut self, key: u64, value: u64) -> Option<u64> {
+            self.insert(key, value)
+        }
+
         fn get(&self, key: &u64) -> Option<&u64> {
             self.get(key)
         }
 
+        fn get_mut(&mut self, key: &u64) -> Option<&mut u64> {
+            self.get_mut(key)
+        }
+
         fn contains(&self, key: &u64) -> bool {
             self.contains_key(key)
         }
+
+        fn len(&self) -> usize {
+            self.len()
+        }
+
+        fn is_empty(&self) -> bool {
+            self.is_empty()
+        }
+
+        fn reserve(&mut self, additional: usize) {
+            self.reserve(additional)
+        }
     }
 
     impl HashTableRemove<u64, u64> for HashMap<u64, u64> {
@@ -182,7 +306,7 @@ pub mod std_btree {
             BTreeMap::new()
         }
 
-        fn try_insert(&mut self, key_value_pair: (u64, u64)) -> Insertion<u64> {
+        fn try_insert(&mut self, key_value_pair: (u64, u64)) -> Insertion<'_, u64> {
             let (key, value) = key_value_pair;
             match self.entry(key) {
                 Entry::Occupied(occ) => Insertion::Occupied(occ.into_mut()),
@@ -190,13 +314,32 @@ pub mod std_btree {
             }
         }
 
+        fn insert_owned(&mut self, key: u64, value: u64) -> Option<u64> {
+            self.insert(key, value)
+        }
+
         fn get(&self, key: &u64) -> Option<&u64> {
             self.get(key)
         }
 
+        fn get_mut(&mut self, key: &u64) -> Option<&mut u64> {
+            self.get_mut(key)
+        }
+
         fn contains(&self, key: &u64) -> bool {
             self.contains_key(key)
         }
+
+        fn len(&self) -> usize {
+            self.len()
+        }
+
+        fn is_empty(&self) -> bool {
+            self.is_empty()
+        }
+
+        // Nodes are allocated as the tree grows, there is no capacity to reserve
+        fn reserve(&mut self, _additional: usize) {}
     }
 
     impl HashTableRemove<u64, u64> for BTreeMap<u64, u64> {
@@ -0,0 +1,111 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, BuildHasherDefault, Hasher};
+
+// Odd multiplier (2^64 divided by the golden ratio)
+const MULTIPLIER: u64 = 0x9E37_79B9_7F4A_7C15;
+// Multiplier of the MurmurHash3 64 bit finalizer
+const FINALIZER_MULTIPLIER: u64 = 0xFF51_AFD7_ED55_8CCD;
+
+/// Multiply-shift hashing for `u32` keys. The key is multiplied by an odd 64 bit constant and
+/// the high bits of the product, which SlickHash uses to pick the block, form the hash.
+#[derive(Clone, Copy, Default)]
+pub struct MultiplyShift32 {
+    hash: u64,
+}
+
+impl Hasher for MultiplyShift32 {
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    // Keys other than u32 are folded in byte by byte
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.write_u32(byte as u32);
+        }
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.hash = (self.hash ^ i as u64).wrapping_mul(MULTIPLIER);
+    }
+}
+
+pub type BuildMultiplyShift32 = BuildHasherDefault<MultiplyShift32>;
+
+/// The default block hasher of SlickHash: words are mixed in with a multiplication by an odd
+/// constant, and the result is spread by a xorshift-multiply finalizer so that the high bits
+/// depend on all input bits. Much cheaper than SipHash for integer keys, but not DoS resistant.
+#[derive(Clone, Copy, Default)]
+pub struct FastMix {
+    hash: u64,
+}
+
+impl Hasher for FastMix {
+    fn finish(&self) -> u64 {
+        let mut hash = self.hash;
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(FINALIZER_MULTIPLIER);
+        hash ^ (hash >> 33)
+    }
+
+    // Other keys are folded in eight bytes at a time
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            self.write_u64(u64::from_le_bytes(chunk.try_into().unwrap()));
+        }
+        let remainder = chunks.remainder();
+        if !remainder.is_empty() {
+            let mut word = [0; 8];
+            word[..remainder.len()].copy_from_slice(remainder);
+            self.write_u64(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ i).wrapping_mul(MULTIPLIER);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+}
+
+/// Builds `FastMix` hashers starting from a seed. The default seed is 0, so that tables hash
+/// reproducibly unless they ask for another seed, e.g. after detecting a hash-flooding attack.
+#[derive(Clone, Copy, Default)]
+pub struct BuildFastMix {
+    seed: u64,
+}
+
+impl BuildFastMix {
+    pub fn with_seed(seed: u64) -> Self {
+        BuildFastMix { seed }
+    }
+
+    /// A builder with a fresh random seed, drawn from the random keys of std's `RandomState`.
+    /// Suited as the reseed function of `SlickHash::set_reseed_on_flood`.
+    pub fn reseeded(&self) -> Self {
+        BuildFastMix { seed: RandomState::new().hash_one(self.seed) }
+    }
+}
+
+impl BuildHasher for BuildFastMix {
+    type Hasher = FastMix;
+
+    fn build_hasher(&self) -> FastMix {
+        FastMix { hash: self.seed }
+    }
+}
@@ -1,71 +1,223 @@
-mod hash_table;
+pub mod hash_table;
+#[cfg(feature = "bench")]
+mod bench;
+mod entry;
+mod hasher;
+mod params;
+#[cfg(test)]
+mod tests;
+mod view;
 
-use std::collections::hash_map::{DefaultHasher, Entry};
+#[cfg(feature = "bench")]
+pub use bench::BuildComparison;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+pub use hasher::{BuildFastMix, BuildMultiplyShift32, FastMix, MultiplyShift32};
+pub use params::{ParamError, SlickParams, ThresholdPolicy};
+use params::DEFAULT_BLOCK_SIZE;
+pub use view::SlickHashView;
+use std::cmp::Reverse;
+use std::collections::hash_map;
 use hash_table::{Capacity, HashTableBase, HashTableBulk, HashTableRemove, Insertion, Named};
-use std::collections::HashMap;
-use std::hash::{Hash, Hasher};
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet, TryReserveError};
+use std::hash::{BuildHasher, Hash};
 use std::ops::Range;
-use std::u64;
-use ahash::AHasher;
 
 pub struct SlickHashMetaData {
     offset: usize,
     gap: usize,
     threshold: usize,
+    // Entries counted independently of offsets and gaps, checked against the block's range
+    #[cfg(debug_assertions)]
+    live_len: usize,
 }
 
-pub struct SlickHash<Key, Value> {
+/// A physical main table slot, as yielded by `SlickHash::physical_layout`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot<Key, Value> {
+    Occupied(Key, Value),
+    Gap,
+}
+
+/// State of one block, as yielded by `SlickHash::iter_blocks`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockSummary {
+    pub index: usize,
+    /// Physical slot range of the block's live entries
+    pub start: usize,
+    pub end: usize,
+    pub live_len: usize,
+    pub offset: usize,
+    pub gap: usize,
+    pub threshold: usize,
+}
+
+// Block index and threshold of a key, both derived from a single hash of it. An operation
+// hashes its key once and passes this along to the helpers it calls.
+#[derive(Clone, Copy)]
+struct HashedKey {
+    hash: u64,
+    block_index: usize,
+    threshold: usize,
+}
+
+// Physical position of a live entry
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Location {
+    MainTable(usize),
+    Backyard,
+}
+
+pub struct SlickHash<Key, Value, S = BuildFastMix> {
     main_table_size: usize,
     block_size: usize,
     number_of_blocks: usize,
     max_slick_size: usize,
     max_offset: usize,
     max_threshold: usize,
+    threshold_policy: ThresholdPolicy,
 
     main_table: Vec<(Key, Value)>,
     meta_data: Vec<SlickHashMetaData>,
     backyard: HashMap<Key, Value>,
-    no_elements_in_main_table: usize
+    no_elements_in_main_table: usize,
+    // Keys placed into the backyard on purpose, bypassing threshold routing. While there are
+    // any, a miss in the block falls back to the backyard. A key is forgotten as soon as it
+    // leaves the backyard, however it is removed.
+    direct_spills: HashSet<Key>,
+    rebalance_on_bulk_remove: bool,
+    refill_gapless_left_on_remove: bool,
+    // Backyard size from which an insertion that would spill grows the table first
+    backyard_soft_limit: Option<usize>,
+    // Set by a resize that left the backyard at its soft limit, where growing again wouldn't
+    // bring it below. The limit rests until the next resize.
+    backyard_limit_suspended: bool,
+    // Operations since the main table was last rebuilt, see needs_compaction
+    insertions_since_compaction: usize,
+    removals_since_compaction: usize,
+    // Derives a new hash builder once flooding is detected, see set_reseed_on_flood
+    reseed: Option<fn(&S) -> S>,
+    reseed_count: usize,
+    // Backyard size from which the next insertion checks for flooding
+    next_flood_check: usize,
+    hash_builder: S,
+}
+
+// Estimated heap bytes of a std HashMap (a SwissTable) able to hold `capacity` entries: a power
+// of two number of buckets kept at most 7/8 full, each holding one entry and one control byte,
+// plus a trailing group of 16 control bytes
+fn estimated_hashmap_bytes<Key, Value>(capacity: usize) -> usize {
+    if capacity == 0 {
+        return 0
+    }
+    let buckets = match capacity {
+        0..4 => 4,
+        4..8 => 8,
+        _ => (capacity * 8).div_ceil(7).next_power_of_two(),
+    };
+    buckets * (std::mem::size_of::<(Key, Value)>() + 1) + 16
+}
+
+// The error of a capacity that doesn't fit into usize, which TryReserveError can't be built
+// from directly
+fn capacity_overflow() -> TryReserveError {
+    Vec::<u8>::new().try_reserve(usize::MAX).unwrap_err()
+}
+
+// Fragmentation past which needs_compaction suggests a rebuild: the fraction of entries in the
+// backyard, and the mean block offset relative to max_offset
+const COMPACTION_BACKYARD_RATIO: f64 = 0.1;
+const COMPACTION_MEAN_OFFSET_RATIO: f64 = 0.5;
+
+// A table counts as flooded once its backyard holds at least this many entries and more than
+// this fraction of all entries. Benign keys at full load leave a few percent in the backyard.
+const FLOOD_MIN_BACKYARD: usize = 64;
+const FLOOD_BACKYARD_RATIO: f64 = 0.25;
+
+// Number of keys whose blocks are prefetched ahead of probing them: enough to overlap the
+// memory latency of their misses, few enough that the lines are still cached when probed
+#[cfg(feature = "prefetch")]
+const PREFETCH_BATCH: usize = 16;
+
+// Hints the CPU to load the cache line at the pointer, which doesn't have to be valid
+#[cfg(feature = "prefetch")]
+#[inline(always)]
+fn prefetch<T>(pointer: *const T) {
+    #[cfg(target_arch = "x86_64")]
+    // SAFETY: a prefetch never faults, not even for invalid addresses
+    unsafe {
+        std::arch::x86_64::_mm_prefetch(pointer as *const i8, std::arch::x86_64::_MM_HINT_T0)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    let _ = pointer;
 }
 
-impl<Key, Value> SlickHash<Key, Value>
+/// SlickHash for `u32` keys, placing them into blocks via multiply-shift hashing
+pub type SlickHash32<Value> = SlickHash<u32, Value, BuildMultiplyShift32>;
+
+impl<Key, Value, S> SlickHash<Key, Value, S>
 where
     Key: Clone + Eq + PartialEq + Hash + Default,
     Value: Clone + Default,
+    S: BuildHasher + Clone,
 {
-    fn new(capacity: usize) -> Self {
-        // Hyper parameters
-        let block_size: usize = 10;
-        let max_slick_size = block_size * 2;
-        let max_offset = block_size;
-        let max_threshold = block_size;
-
-        // Other setup
+    /// Creates a table whose keys are assigned to blocks using the given hash builder
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        Self::with_params_and_hasher(capacity, SlickParams::default(), hash_builder)
+    }
+
+    /// Creates a table with the given hyperparameters whose keys are assigned to blocks using the
+    /// given hash builder
+    pub fn with_params_and_hasher(capacity: usize, params: SlickParams, hash_builder: S) -> Self {
+        Self::try_allocate(capacity, params, hash_builder).expect("allocation of the main table failed")
+    }
+
+    // Sets up an empty table, reporting a failed allocation instead of aborting
+    fn try_allocate(capacity: usize, params: SlickParams, hash_builder: S) -> Result<Self, TryReserveError> {
+        let SlickParams { block_size, max_slick_size, max_offset, max_threshold, threshold_policy } = params;
+
         let main_table_size = capacity;
         assert_eq!(main_table_size % block_size, 0);
         let number_of_blocks: usize = main_table_size / block_size;
-        let main_table: Vec<(Key, Value)> = vec![Default::default(); capacity];
-        let mut meta_data: Vec<SlickHashMetaData> = Vec::with_capacity(number_of_blocks);
+        let mut main_table: Vec<(Key, Value)> = Vec::new();
+        main_table.try_reserve_exact(capacity)?;
+        main_table.resize(capacity, Default::default());
+        let mut meta_data: Vec<SlickHashMetaData> = Vec::new();
+        meta_data.try_reserve_exact(number_of_blocks)?;
         for _ in 0..number_of_blocks {
             meta_data.push(SlickHashMetaData {
                 offset: 0,
                 gap: block_size,
                 threshold: 0,
+                #[cfg(debug_assertions)]
+                live_len: 0,
             })
         }
 
-        Self {
+        Ok(Self {
             main_table_size,
             block_size,
             number_of_blocks,
             max_slick_size,
             max_offset,
             max_threshold,
+            threshold_policy,
             main_table,
             meta_data,
             backyard: HashMap::new(),
-            no_elements_in_main_table: 0
-        }
+            no_elements_in_main_table: 0,
+            direct_spills: HashSet::new(),
+            rebalance_on_bulk_remove: true,
+            refill_gapless_left_on_remove: false,
+            backyard_soft_limit: None,
+            backyard_limit_suspended: false,
+            insertions_since_compaction: 0,
+            removals_since_compaction: 0,
+            reseed: None,
+            reseed_count: 0,
+            next_flood_check: FLOOD_MIN_BACKYARD,
+            hash_builder,
+        })
     }
 
     fn block_start(&self, block_index: usize) -> usize {
@@ -81,24 +233,39 @@ where
         self.block_size * block_index + self.block_size + self.meta_data[block_index+1].offset - self.meta_data[block_index].gap
     }
 
+    // Slots of the block's live entries. Every scan stays within this range and never reads
+    // a gap slot, which holds a default or stale pair, so `Key::default()` is a key like any
+    // other and a gap can't be mistaken for it.
     fn block_range(&self, block_index: usize) -> Range<usize> {
         let start = self.block_start(block_index);
         let end = self.block_end(block_index);
-        return start..end
+        start..end
+    }
+
+    /// Physical slot range of a block's live entries, or None for an out-of-range block index
+    pub fn block_range_checked(&self, block_index: usize) -> Option<Range<usize>> {
+        (block_index < self.number_of_blocks).then(|| self.block_range(block_index))
+    }
+
+    /// The block's live entries in physical order, e.g. to check the exact outcome of a bump.
+    /// Panics for an out-of-range block index.
+    pub fn raw_block_slots(&self, block_index: usize) -> &[(Key, Value)] {
+        &self.main_table[self.block_range(block_index)]
     }
 
-    fn insert_into_backyard(&mut self, key: Key, value: Value) -> Insertion<Value> {
+    fn insert_into_backyard(&mut self, key: Key, value: Value) -> Insertion<'_, Value> {
         match self.backyard.entry(key) {
-            Entry::Occupied(occ) => Insertion::Occupied(occ.into_mut()),
-            Entry::Vacant(vac) => Insertion::Inserted(vac.insert(value)),
+            hash_map::Entry::Occupied(occ) => Insertion::Occupied(occ.into_mut()),
+            hash_map::Entry::Vacant(vac) => Insertion::Inserted(vac.insert(value)),
         }
     }
 
-    fn slide_gap_from_left(&mut self, block_index: usize) -> bool {
+    // Finds the block whose gap slide_gap_from_left would move, without modifying anything
+    fn find_gap_on_left(&self, block_index: usize) -> Option<usize> {
         let mut sliding_block_index = block_index;
         while self.meta_data[sliding_block_index].gap == 0 {
             if (sliding_block_index == 0) || (self.meta_data[sliding_block_index].offset == 0) {
-                return false
+                return None
             }
             sliding_block_index -= 1;
         }
@@ -107,8 +274,17 @@ where
         // In this case, sliding gap from left is not possible
         let empty_block_has_gap_one = (self.meta_data[sliding_block_index].gap == 1) && (self.block_start(sliding_block_index) == self.block_end(sliding_block_index));
         if empty_block_has_gap_one {
-            return false
+            return None
         }
+        Some(sliding_block_index)
+    }
+
+    // All or nothing: every condition is checked by find_gap_on_left before the first write,
+    // so a refused slide leaves the table untouched and an accepted one can't stop halfway
+    fn slide_gap_from_left(&mut self, block_index: usize) -> bool {
+        let Some(mut sliding_block_index) = self.find_gap_on_left(block_index) else {
+            return false
+        };
 
         // A gap in block at sliding_block_index has been found
 
@@ -118,45 +294,59 @@ where
             // Extends the block by one space ont the left and fills the free spot at the front with the element at the back
             let start_sliding_block = self.block_start(sliding_block_index);
             let end_sliding_block = self.block_end(sliding_block_index);
+            debug_assert!(self.meta_data[sliding_block_index].offset > 0, "slide from left was not checked");
             self.main_table[start_sliding_block-1] = self.main_table[end_sliding_block-1].clone();
             self.meta_data[sliding_block_index].offset -= 1;
             sliding_block_index += 1;
         }
         self.meta_data[sliding_block_index-1].gap += 1;
-        return true;
+        true
     }
 
-    fn slide_gap_from_right(&mut self, block_index: usize) -> bool {
+    // Finds the block whose gap slide_gap_from_right would move, without modifying anything
+    fn find_gap_on_right(&self, block_index: usize) -> Option<usize> {
         if block_index == self.number_of_blocks-1 {
-            return false;
+            return None;
         }
 
         let mut sliding_block_index = block_index + 1;
         while self.meta_data[sliding_block_index].gap == 0 {
             if (sliding_block_index == self.number_of_blocks-1) ||
                 (self.meta_data[sliding_block_index].offset == self.max_offset) {
-                return false;
+                return None;
             }
             sliding_block_index += 1;
         }
 
         // Enforcing the maximum offset
         if self.meta_data[sliding_block_index].offset == self.max_offset {
-            return false
+            return None
         }
 
         // If the block only has a gap of one and is empty, it would be squished :(
         // In this case, sliding gap from right is not possible
         let empty_block_has_gap_one = (self.meta_data[sliding_block_index].gap == 1) && (self.block_start(sliding_block_index) == self.block_end(sliding_block_index));
         if empty_block_has_gap_one {
-            return false
+            return None
         }
 
+        Some(sliding_block_index)
+    }
+
+    // All or nothing like slide_gap_from_left, with find_gap_on_right doing all checks
+    fn slide_gap_from_right(&mut self, block_index: usize) -> bool {
+        let Some(mut sliding_block_index) = self.find_gap_on_right(block_index) else {
+            return false
+        };
+
         // A gap in block at sliding_block_index has been found
 
         // Unwrapping the first loop execution to reduce the gap of the right-most sliding block
         let start_sliding_block = self.block_start(sliding_block_index);
         let end_sliding_block = self.block_end(sliding_block_index);
+        // The front element moves into the first gap slot, which lies inside the table since the
+        // block has a gap, and even the last block's gap ends at the end of the table
+        debug_assert!(end_sliding_block < self.main_table_size, "slide from right was not checked");
         self.main_table[end_sliding_block] = self.main_table[start_sliding_block].clone();
 
         self.meta_data[sliding_block_index].offset += 1;
@@ -172,26 +362,144 @@ where
             // again because the offset of the successive block has already been updated
             self.main_table[end_sliding_block-1] = self.main_table[start_sliding_block].clone();
 
+            debug_assert!(self.meta_data[sliding_block_index].offset < self.max_offset, "slide from right was not checked");
             self.meta_data[sliding_block_index].offset += 1;
             sliding_block_index -= 1;
         }
         self.meta_data[sliding_block_index].gap += 1;
-        return true;
+        true
     }
 
-    fn hash_block_index(&self, key: &Key) -> usize {
-        let mut hasher = DefaultHasher::new();
-        key.hash(&mut hasher);
-        let hash = hasher.finish() as f64;
+    // Moves a block one slot back towards its nominal start by taking over the last gap slot
+    // of the block to its left, relocating the block's last entry to the freed front slot
+    fn shift_block_left(&mut self, block_index: usize) {
+        let start_block = self.block_start(block_index);
+        let end_block = self.block_end(block_index);
+        self.main_table[start_block-1] = self.main_table[end_block-1].clone();
+        self.meta_data[block_index].offset -= 1;
+        self.meta_data[block_index-1].gap -= 1;
+        self.meta_data[block_index].gap += 1;
+    }
+
+    // Inverse of shift_block_left: moves a block one slot further from its nominal start,
+    // handing its first slot to the gap of the block to its left and relocating the block's
+    // first entry into its own gap
+    fn shift_block_right(&mut self, block_index: usize) {
+        let start_block = self.block_start(block_index);
+        let end_block = self.block_end(block_index);
+        self.main_table[end_block] = self.main_table[start_block].clone();
+        self.meta_data[block_index].offset += 1;
+        self.meta_data[block_index].gap -= 1;
+        self.meta_data[block_index-1].gap += 1;
+    }
+
+    // Hands a slot of the block to its left neighbour if that one has no gap left. This is a
+    // heuristic: a gapless neighbour has often lent its last slot in a slide from the left, but
+    // may just as well be full on its own. The block keeps one free slot either way.
+    fn refill_gapless_left_neighbour(&mut self, block_index: usize) {
+        if block_index == 0 {
+            return
+        }
+        let can_return = self.meta_data[block_index-1].gap == 0
+            && self.meta_data[block_index].gap > 1
+            && self.meta_data[block_index].offset < self.max_offset;
+        if can_return {
+            self.shift_block_right(block_index);
+        }
+    }
+
+    // Re-centers a block's offset towards 0 while its left neighbour has slack to spare,
+    // so that blocks don't drift up to max_offset where sliding from the right stops working
+    fn reconcile_offset(&mut self, block_index: usize) {
+        if block_index == 0 || block_index >= self.number_of_blocks {
+            return
+        }
+        // The left neighbour keeps one free slot so that it isn't squished
+        while self.meta_data[block_index].offset > 0 && self.meta_data[block_index-1].gap > 1 {
+            self.shift_block_left(block_index);
+        }
+    }
+
+    fn hash_key<Q: Hash + ?Sized>(&self, key: &Q) -> HashedKey {
+        self.hashed_from(self.hash_builder.hash_one(key))
+    }
+
+    // Derives block index and threshold from the hash. Also used to place a key again after
+    // the table has been resized, without hashing it anew.
+    fn hashed_from(&self, hash: u64) -> HashedKey {
+        let block_index = self.block_index_from_hash(hash);
+        // Every insert, lookup and removal derives its block here, so this catches an index
+        // past the last block before it is used to index the metadata
+        debug_assert!(block_index < self.number_of_blocks.max(1), "hash {hash:#x} maps past the last block");
+        HashedKey {
+            hash,
+            block_index,
+            threshold: self.threshold_from_hash(hash),
+        }
+    }
+
+    fn block_index_from_hash(&self, hash: u64) -> usize {
+        // Multiply-shift: scales the hash to [0, number_of_blocks) without rounding, which the
+        // former float scaling did for hashes close to u64::MAX, yielding number_of_blocks. For
+        // a power of two blocks, this is just the top log2(number_of_blocks) bits of the hash.
+        ((hash as u128 * self.number_of_blocks as u128) >> 64) as usize
+    }
+
+    fn threshold_from_hash(&self, hash: u64) -> usize {
+        // Remixes the hash (SplitMix64 finalizer) so that the threshold doesn't depend on the
+        // same bits as the block index
+        let mut mixed = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        mixed ^= mixed >> 31;
+        ((mixed as u128 * self.max_threshold as u128) >> 64) as usize
+    }
 
-        ((hash / (u64::MAX as f64)) * self.number_of_blocks as f64) as usize
+    fn hash_block_index(&self, key: &Key) -> usize {
+        self.hash_key(key).block_index
     }
 
     fn hash_threshold(&self, key: &Key) -> usize {
-        let mut hasher = AHasher::default();
-        key.hash(&mut hasher);
-        let hash = hasher.finish() as f64;
-        ((hash / (u64::MAX as f64)) * self.max_threshold as f64) as usize
+        self.hash_key(key).threshold
+    }
+
+    // Calculates t prime, the new threshold of a block without space, as chosen by the threshold
+    // policy: never below one above the smallest threshold of all keys present and the key to
+    // add, so that at least one of them is bumped
+    fn bump_threshold(&self, block_range: &Range<usize>, key_threshold: usize) -> usize {
+        let mut min_threshold_hash = self.max_threshold+1;
+        // Find the smallest threshold of all keys present
+        for (iter_key, _) in &self.main_table[block_range.clone()] {
+            // Find the key with the minimum hash
+            let iter_key_threshold = self.hash_threshold(iter_key);
+            if iter_key_threshold < min_threshold_hash {
+                min_threshold_hash = iter_key_threshold;
+            }
+        }
+
+        // Asserting that there has been found a minimum threshold
+        assert!(min_threshold_hash < self.max_threshold+1);
+
+        // Check if the threshold of the key to add is the smallest
+        if key_threshold < min_threshold_hash {
+            min_threshold_hash = key_threshold;
+        }
+        let sorted_thresholds = || {
+            let mut thresholds: Vec<usize> = self.main_table[block_range.clone()].iter()
+                .map(|(iter_key, _)| self.hash_threshold(iter_key))
+                .collect();
+            thresholds.push(key_threshold);
+            thresholds.sort_unstable();
+            thresholds
+        };
+        let t_prime = match self.threshold_policy {
+            ThresholdPolicy::Minimal => return min_threshold_hash + 1,
+            ThresholdPolicy::Aggressive => {
+                let thresholds = sorted_thresholds();
+                thresholds[thresholds.len() / 2]
+            }
+            ThresholdPolicy::Custom(policy) => policy(&sorted_thresholds()),
+        };
+        t_prime.max(min_threshold_hash + 1)
     }
 
     fn there_is_no_space(&mut self, block_range: &Range<usize>, block_index: usize) -> bool {
@@ -202,28 +510,57 @@ where
                     self.slide_gap_from_right(block_index)
             )
     }
-}
 
-impl<Key, Value> HashTableBase<Key, Value> for SlickHash<Key, Value>
-where
-    Key: Clone + Eq + PartialEq + Hash + Default,
-    Value: Clone + Default,
-{
-    fn with_capacity(capacity: impl Capacity) -> Self {
-        SlickHash::new(capacity.capacity())
+    // Whether inserting the absent key grows the table first: once the main table could hold
+    // every entry, or once the key would spill into a backyard that has reached its soft limit
+    fn needs_growth_for(&self, hashed: HashedKey) -> bool {
+        let backyard_is_full = !self.backyard_limit_suspended
+            && self.backyard_soft_limit.is_some_and(|limit| self.backyard.len() >= limit);
+        self.len() >= self.main_table_size || (backyard_is_full && self.would_spill(hashed))
     }
 
-    fn try_insert(&mut self, key_value_pair: (Key, Value)) -> Insertion<Value> {
-        let (key, value) = key_value_pair;
-        let block_index = self.hash_block_index(&key);
+    // Whether inserting a new key would put it or bumped keys into the backyard, without
+    // modifying anything
+    fn would_spill(&self, hashed: HashedKey) -> bool {
+        let block_index = hashed.block_index;
+        hashed.threshold < self.meta_data[block_index].threshold
+            || self.block_range(block_index).len() >= self.max_slick_size
+            || (self.meta_data[block_index].gap == 0
+                && self.find_gap_on_left(block_index).is_none()
+                && self.find_gap_on_right(block_index).is_none())
+    }
+
+    // Inserts the pair into its block, sliding, bumping and growing as needed
+    fn insert_pair(&mut self, key: Key, value: Value) -> Insertion<'_, Value> {
+        let hashed = self.hash_key(&key);
+        self.insert_hashed(key, hashed, value)
+    }
+
+    fn insert_hashed(&mut self, key: Key, mut hashed: HashedKey, value: Value) -> Insertion<'_, Value> {
+        // Without blocks, only directly spilled entries exist. A present key is answered from
+        // the backyard like in get, a new one grows the table below.
+        if self.number_of_blocks == 0 && self.backyard.contains_key(&key) {
+            self.insertions_since_compaction += 1;
+            return self.insert_into_backyard(key, value)
+        }
+        // Only a new key makes the table grow
+        if self.needs_growth_for(hashed) && self.locate_hashed(&key, hashed).is_none() {
+            self.resize_to(self.grow_hint());
+            hashed = self.hashed_from(hashed.hash);
+        }
+        if self.reseed_if_flooded() {
+            hashed = self.hash_key(&key);
+        }
+        self.insertions_since_compaction += 1;
+        let block_index = hashed.block_index;
         let block_start = self.block_start(block_index);
         let block_range = self.block_range(block_index);
-        if self.hash_threshold(&key) < self.meta_data[block_index].threshold {
+        if hashed.threshold < self.meta_data[block_index].threshold {
             return self.insert_into_backyard(key, value);
         }
 
         // Searches for the value in the main table, returns a mutable reference on the value on find
-        if block_range.len() > 0 {
+        if !block_range.is_empty() {
             // Finds the index if the key is in the block, else the index stays None
             let block_range_elements_as_mut = &self.main_table[block_range.clone()];
             let mut found_index = None;
@@ -236,36 +573,71 @@ where
 
             // Returns a mutable reference on the value if the key is found
             if let Some(some_found_index) = found_index {
-                return Insertion::Inserted(&mut self.main_table[some_found_index].1)
+                return Insertion::Occupied(&mut self.main_table[block_start + some_found_index].1)
             }
         }
+        if !self.direct_spills.is_empty() && self.backyard.contains_key(&key) {
+            return self.insert_into_backyard(key, value)
+        }
+        self.insert_absent_without_growth(key, hashed, value)
+    }
+
+    // Replaces the hash builder with a reseeded one and rehashes all entries if the backyard
+    // has filled up with keys that collide, see set_reseed_on_flood. Returns whether it did,
+    // which invalidates all hashes computed so far.
+    fn reseed_if_flooded(&mut self) -> bool {
+        let Some(reseed) = self.reseed else {
+            return false
+        };
+        let backyard_len = self.backyard.len();
+        if backyard_len < self.next_flood_check || (backyard_len as f64) <= FLOOD_BACKYARD_RATIO * self.len() as f64 {
+            return false
+        }
+        self.set_hasher(reseed(&self.hash_builder));
+        self.reseed_count += 1;
+        // Keys that collide under every seed could otherwise make each insertion rehash
+        self.next_flood_check = 2 * backyard_len;
+        true
+    }
+
+    // Inserts a key the caller has already found to be absent, like a vacant entry does, so
+    // that neither its block nor the backyard is searched for it again
+    fn insert_absent(&mut self, key: Key, mut hashed: HashedKey, value: Value) -> Insertion<'_, Value> {
+        if self.needs_growth_for(hashed) {
+            self.resize_to(self.grow_hint());
+            hashed = self.hashed_from(hashed.hash);
+        }
+        if self.reseed_if_flooded() {
+            hashed = self.hash_key(&key);
+        }
+        self.insertions_since_compaction += 1;
+        self.insert_absent_without_growth(key, hashed, value)
+    }
+
+    // Inserts a key known to be absent, like insert_hashed but without ever growing, so that
+    // block indices hashed in advance stay valid. Without room in its block, the key is bumped
+    // like any other.
+    fn insert_absent_without_growth(&mut self, key: Key, hashed: HashedKey, value: Value) -> Insertion<'_, Value> {
+        let block_index = hashed.block_index;
+        if hashed.threshold < self.meta_data[block_index].threshold {
+            return self.insert_into_backyard(key, value)
+        }
+        let block_start = self.block_start(block_index);
+        let block_range = self.block_range(block_index);
 
         // Bumps elements if there is no space or no space can be made by sliding
         // If the block is too large or there is no empty slot usable in the table
         let there_is_no_space = self.there_is_no_space(&block_range, block_index);
         if there_is_no_space
         {
-            let mut min_threshold_hash = self.max_threshold+1;
-            // Calculating t prime
-            // Find the smallest threshold of all keys present
-            for (iter_key, _) in &self.main_table[block_range.clone()] {
-                // Find the key with the minimum hash
-                let key_threshold = self.hash_threshold(iter_key);
-                if key_threshold < min_threshold_hash {
-                    min_threshold_hash = key_threshold;
-                }
-            }
+            // No slide happened when there is no space, so block_start and block_range are
+            // still current
+            let t_prime = self.bump_threshold(&block_range, hashed.threshold);
 
-            // Asserting that there has been found a minimum threshold
-            assert!(min_threshold_hash < self.max_threshold+1);
-
-            // Check if the threshold of the key to add is the smallest
-            if self.hash_threshold(&key) < min_threshold_hash {
-                min_threshold_hash = self.hash_threshold(&key);
-            }
-            let t_prime = min_threshold_hash + 1;
-
-            // Scans the existing elements and bumps them if necessary
+            // Scans the existing elements and bumps them if necessary. Every key below t_prime,
+            // the bumped ones as well as the input key, ends up in the backyard, which is exactly
+            // where get looks for keys below the raised threshold. The input key is absent from
+            // both storages, so it can't collide with a bumped key.
             self.meta_data[block_index].threshold = t_prime;
             let mut j = block_start;
             let mut block_end = self.block_end(block_index);
@@ -273,83 +645,1514 @@ where
                 let (iter_key, iter_value) = &self.main_table[j];
                 let key_threshold = self.hash_threshold(iter_key);
                 if key_threshold < t_prime {
-                    self.insert_into_backyard(iter_key.clone(), iter_value.clone());
+                    let _ = self.insert_into_backyard(iter_key.clone(), iter_value.clone());
                     self.no_elements_in_main_table -= 1;
                     self.main_table[j] = self.main_table[block_end-1].clone();
                     self.meta_data[block_index].gap += 1;
+                    #[cfg(debug_assertions)]
+                    { self.meta_data[block_index].live_len -= 1; }
                     block_end = self.block_end(block_index);
                 } else {
                     j += 1;
                 }
             }
+            self.debug_assert_live_len(block_index);
             // Bumps the input key-value pair into the backyard if necessary
-            if self.hash_threshold(&key) < t_prime {
+            if hashed.threshold < t_prime {
                 return self.insert_into_backyard(key, value)
             }
         }
         // Inserts the input key-value pair at the end of the block and reduces the block's gap by 1
-        let current_block_end = self.block_end(block_index);
-        self.main_table[current_block_end] = (key, value);
+        self.insert_at_block_end(block_index, key, value)
+    }
+
+    // Appends the pair behind the block's last entry, using up one slot of its gap
+    fn insert_at_block_end(&mut self, block_index: usize, key: Key, value: Value) -> Insertion<'_, Value> {
+        let block_end = self.block_end(block_index);
+        self.main_table[block_end] = (key, value);
         self.no_elements_in_main_table += 1;
         self.meta_data[block_index].gap -= 1;
+        #[cfg(debug_assertions)]
+        { self.meta_data[block_index].live_len += 1; }
+        self.debug_assert_live_len(block_index);
+        Insertion::Inserted(&mut self.main_table[block_end].1)
+    }
 
-        // Displaying the number of elements in the table at the end, assuming the number of inserted elements is 2,000,000
-        if self.no_elements_in_main_table + self.backyard.len() == 2_000_000 {
-            println!("Final number of elements in main table: {}", self.no_elements_in_main_table);
-            println!("Final number of elements in backyard table: {}", self.backyard.len());
+    // Removes the key from the backyard, forgetting it if it was spilled there directly
+    fn remove_from_backyard(&mut self, key: &Key) -> Option<(Key, Value)> {
+        let key_value_pair = self.backyard.remove_entry(key)?;
+        if !self.direct_spills.is_empty() {
+            self.direct_spills.remove(key);
         }
+        Some(key_value_pair)
+    }
 
-        return Insertion::Inserted(&mut self.main_table[current_block_end].1);
+    // Ties the block's slot range to its independently counted entries in debug builds. A gap
+    // that is off by one makes the range take in a stale slot or drop a live entry.
+    fn debug_assert_live_len(&self, block_index: usize) {
+        #[cfg(debug_assertions)]
+        assert_eq!(self.block_range(block_index).len(), self.meta_data[block_index].live_len,
+                   "slot range of block {block_index} disagrees with its number of entries");
+        #[cfg(not(debug_assertions))]
+        let _ = block_index;
     }
 
-    fn get(&self, key: &Key) -> Option<&Value> {
-        let block_index = self.hash_block_index(key);
-        if self.hash_threshold(key) < self.meta_data[block_index].threshold {
+    // Removes the key from wherever it lives, closing the hole in its block
+    fn take_entry(&mut self, key: &Key) -> Option<(Key, Value)> {
+        if self.number_of_blocks == 0 {
+            return self.remove_from_backyard(key)
+        }
+        let hashed = self.hash_key(key);
+        let block_index = hashed.block_index;
+        // Keys below the block's threshold can only live in the backyard, so an absent key
+        // must not touch the block or the element counter
+        if hashed.threshold < self.meta_data[block_index].threshold {
+            return self.remove_from_backyard(key)
+        }
+        for i in self.block_range(block_index) {
+            if *key == self.main_table[i].0 {
+                let key_value_pair = self.main_table[i].clone();
+                // The block's last entry fills the hole and the gap grows by the slot it leaves,
+                // so the moved entry stays inside the block's range, which only shrinks at its
+                // end. The slides below move whole blocks and never change what belongs to one.
+                self.main_table[i] = self.main_table[self.block_end(block_index)-1].clone();
+                self.meta_data[block_index].gap += 1;
+                #[cfg(debug_assertions)]
+                { self.meta_data[block_index].live_len -= 1; }
+                self.no_elements_in_main_table -= 1;
+                // The freed slot may be handed back to the right neighbour that lent it
+                self.reconcile_offset(block_index + 1);
+                if self.refill_gapless_left_on_remove {
+                    self.refill_gapless_left_neighbour(block_index);
+                }
+                self.debug_assert_live_len(block_index);
+                return Some(key_value_pair)
+            }
+        }
+        if !self.direct_spills.is_empty() {
+            return self.remove_from_backyard(key)
+        }
+        None
+    }
+
+    // Finds where a key lives, following the same threshold routing as get
+    fn locate(&self, key: &Key) -> Option<Location> {
+        self.locate_hashed(key, self.hash_key(key))
+    }
+
+    fn locate_hashed<Q>(&self, key: &Q, hashed: HashedKey) -> Option<Location>
+    where
+        Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        // Without blocks, only directly spilled entries exist
+        if self.number_of_blocks == 0 {
+            return self.backyard.contains_key(key).then_some(Location::Backyard)
+        }
+        let block_index = hashed.block_index;
+        if hashed.threshold < self.meta_data[block_index].threshold {
+            return self.backyard.contains_key(key).then_some(Location::Backyard)
+        }
+        let location = self.block_range(block_index)
+            .find(|&index| self.main_table[index].0.borrow() == key)
+            .map(Location::MainTable);
+        if location.is_none() && !self.direct_spills.is_empty() && self.backyard.contains_key(key) {
+            return Some(Location::Backyard)
+        }
+        location
+    }
+
+    fn get_hashed<Q>(&self, key: &Q, hashed: HashedKey) -> Option<&Value>
+    where
+        Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.number_of_blocks == 0 {
+            return self.backyard.get(key)
+        }
+        let block_index = hashed.block_index;
+        // Keys below the block's threshold can only live in the backyard, so the block isn't
+        // scanned. All others are looked for in the block only, unless entries were spilled
+        // into the backyard directly.
+        if hashed.threshold < self.meta_data[block_index].threshold {
             return self.backyard.get(key)
         }
         let block_range = self.block_range(block_index);
         let key_value_in_main_table = self.main_table[block_range]
-            .into_iter()
-            .find(|&key_value_pair| key_value_pair.0 == *key);
+            .iter()
+            .find(|&key_value_pair| key_value_pair.0.borrow() == key);
         match key_value_in_main_table {
             Some(kvp) => Some(&kvp.1),
+            None if !self.direct_spills.is_empty() => self.backyard.get(key),
             None => None,
         }
     }
+
+    // Local rebalance of blocks that lost entries: offsets are re-centered, and thresholds are
+    // lowered back to 0 with the blocks' backyard entries reinserted, so that they only spill
+    // again if the blocks are still short of space. Directly spilled entries stay where they
+    // were put.
+    fn rebalance_blocks(&mut self, affected: &[bool]) {
+        let affected_blocks: Vec<usize> = (0..self.number_of_blocks).filter(|&block_index| affected[block_index]).collect();
+        for &block_index in &affected_blocks {
+            self.defragment_block(block_index);
+        }
+        let reinserted_keys: Vec<Key> = self.backyard
+            .keys()
+            .filter(|&key| affected[self.hash_block_index(key)] && !self.direct_spills.contains(key))
+            .cloned()
+            .collect();
+        for &block_index in &affected_blocks {
+            self.meta_data[block_index].threshold = 0;
+        }
+        // Moving entries back isn't counted as insertions
+        let insertions_since_compaction = self.insertions_since_compaction;
+        for key in reinserted_keys {
+            if let Some((key, value)) = self.remove_from_backyard(&key) {
+                let hashed = self.hash_key(&key);
+                let _ = self.insert_absent_without_growth(key, hashed, value);
+            }
+        }
+        self.insertions_since_compaction = insertions_since_compaction;
+    }
+
+    // Rounds a capacity up to the next multiple of the block size
+    fn aligned_capacity(&self, capacity: usize) -> usize {
+        capacity.div_ceil(self.block_size) * self.block_size
+    }
+
+    // Rehashes all entries into a freshly set up table of the given capacity. They are inserted
+    // block by block with thresholds descending, so the resulting placement depends only on the
+    // set of keys, not on the table's history, and a block that runs out of space bumps exactly
+    // the keys with the lowest thresholds. The entries are hashed against the resized table up
+    // front, so it must be large enough to hold all of them without growing again.
+    fn resize_to(&mut self, capacity: usize) {
+        debug_assert!(capacity >= self.len(), "resized table is too small for the entries");
+        let resized = Self::with_params_and_hasher(capacity, self.params(), self.hash_builder.clone());
+        let hashed_entries = Vec::with_capacity(self.len());
+        self.resize_into(resized, hashed_entries);
+    }
+
+    // Moves all entries into the empty resized table, using the given buffer, which has room
+    // for all of them, to sort them. Nothing else is allocated apart from backyard growth.
+    fn resize_into(&mut self, mut resized: Self, mut hashed_entries: Vec<(HashedKey, Key, Value)>) {
+        resized.rebalance_on_bulk_remove = self.rebalance_on_bulk_remove;
+        resized.refill_gapless_left_on_remove = self.refill_gapless_left_on_remove;
+        resized.backyard_soft_limit = self.backyard_soft_limit;
+        resized.reseed = self.reseed;
+        resized.reseed_count = self.reseed_count;
+        resized.next_flood_check = self.next_flood_check;
+        let mut old = std::mem::replace(self, resized);
+        for block_index in 0..old.number_of_blocks {
+            let block_range = old.block_range(block_index);
+            for (key, value) in old.main_table[block_range].iter_mut().map(std::mem::take) {
+                hashed_entries.push((self.hash_key(&key), key, value));
+            }
+        }
+        for (key, value) in old.backyard.drain() {
+            hashed_entries.push((self.hash_key(&key), key, value));
+        }
+        hashed_entries.sort_unstable_by_key(|(hashed, _, _)| (hashed.block_index, Reverse(hashed.threshold), hashed.hash));
+        for (hashed, key, value) in hashed_entries {
+            let _ = self.insert_absent_without_growth(key, hashed, value);
+        }
+        // A backyard that is still at its limit (or, for a limit of 0, not empty) after the
+        // resize wouldn't shrink by growing further
+        self.backyard_limit_suspended = self.backyard_soft_limit.is_some_and(|limit| self.backyard.len() >= limit.max(1));
+        self.insertions_since_compaction = 0;
+    }
+
+    // Moves all live entries out of the main table blocks and the backyard
+    fn into_entries(mut self) -> Vec<(Key, Value)> {
+        self.take_entries()
+    }
+
+    // Moves all live entries out, skipping gap slots, and leaves the table empty with its
+    // capacity kept. Every block is reset only after all entries have been moved out.
+    fn take_entries(&mut self) -> Vec<(Key, Value)> {
+        let mut entries = Vec::with_capacity(self.no_elements_in_main_table + self.backyard.len());
+        for block_index in 0..self.number_of_blocks {
+            let block_range = self.block_range(block_index);
+            entries.extend(self.main_table[block_range].iter_mut().map(std::mem::take));
+        }
+        entries.extend(self.backyard.drain());
+        for meta_data in &mut self.meta_data {
+            *meta_data = SlickHashMetaData {
+                offset: 0,
+                gap: self.block_size,
+                threshold: 0,
+                #[cfg(debug_assertions)]
+                live_len: 0,
+            };
+        }
+        self.no_elements_in_main_table = 0;
+        self.direct_spills.clear();
+        self.backyard_limit_suspended = false;
+        self.insertions_since_compaction = 0;
+        self.removals_since_compaction = 0;
+        entries
+    }
+
+    /// Removes all entries and yields them in arbitrary order, keeping the capacity. The
+    /// entries are moved out before the first one is yielded, so the table is already empty
+    /// and usable while the iterator is alive, and stays so if the consumer panics or drops
+    /// the iterator partway; entries not yet yielded are dropped along with it.
+    pub fn drain(&mut self) -> std::vec::IntoIter<(Key, Value)> {
+        self.take_entries().into_iter()
+    }
+
+    // All live entries, block by block, followed by the backyard
+    fn entries(&self) -> impl Iterator<Item = (&Key, &Value)> + '_ {
+        (0..self.number_of_blocks)
+            .flat_map(move |block_index| self.main_table[self.block_range(block_index)].iter())
+            .map(|(key, value)| (key, value))
+            .chain(self.backyard.iter())
+    }
+
+    /// All entries in arbitrary order: block by block, followed by the backyard. Each block's
+    /// range is read from its metadata when the iterator reaches it, and the table can't change
+    /// while the iterator borrows it, so only live entries are yielded, never gap slots.
+    pub fn iter(&self) -> impl Iterator<Item = (&Key, &Value)> + '_ {
+        self.entries()
+    }
+
+    /// Number of entries stored in the main table and the backyard
+    pub fn len(&self) -> usize {
+        self.no_elements_in_main_table + self.backyard.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Capacity the table would grow to if a new key were inserted now, without resizing.
+    /// Growth doubles the main table, or more if the current entries require it.
+    pub fn grow_hint(&self) -> usize {
+        self.aligned_capacity((self.main_table_size * 2).max(self.len() + 1))
+    }
+
+    /// Grows the main table, if necessary, so that `additional` more entries fit without
+    /// triggering growth during their insertion
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len() + additional;
+        if required > self.main_table_size {
+            self.resize_to(self.aligned_capacity(required).max(self.grow_hint()));
+        }
+    }
+
+    /// Like `reserve`, but grows to the smallest whole number of blocks holding `additional`
+    /// more entries instead of at least doubling. Repeated calls therefore rehash every time,
+    /// so prefer `reserve` unless the final size is known.
+    pub fn reserve_exact(&mut self, additional: usize) {
+        let required = self.len() + additional;
+        if required > self.main_table_size {
+            self.resize_to(self.aligned_capacity(required));
+        }
+    }
+
+    /// Like `reserve`, but returns an error instead of aborting if memory for the grown table
+    /// can't be allocated. All memory the growth needs is allocated before any entry is moved,
+    /// so on failure the table is left exactly as it was.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let required = self.len().checked_add(additional).ok_or_else(capacity_overflow)?;
+        if required <= self.main_table_size {
+            return Ok(())
+        }
+        let capacity = required.checked_next_multiple_of(self.block_size).ok_or_else(capacity_overflow)?;
+        let mut resized = Self::try_allocate(capacity.max(self.grow_hint()), self.params(), self.hash_builder.clone())?;
+        resized.backyard.try_reserve(self.backyard.len())?;
+        let mut hashed_entries = Vec::new();
+        hashed_entries.try_reserve_exact(self.len())?;
+        self.resize_into(resized, hashed_entries);
+        Ok(())
+    }
+
+    /// Shrinks the main table to the smallest capacity that holds all entries
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    /// Shrinks the main table to no less than min_capacity slots, rounded up to the block size,
+    /// and no less than the number of entries (or a single block for an empty table).
+    /// Thresholds are derived anew either way: a table that is already smaller keeps its size,
+    /// but its blocks with raised thresholds are rebalanced, taking back the backyard entries
+    /// that fit again.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let capacity = self.aligned_capacity(min_capacity.max(self.len()).max(1));
+        if capacity < self.main_table_size {
+            self.resize_to(capacity);
+            return
+        }
+        let raised: Vec<bool> = self.meta_data.iter().map(|meta_data| meta_data.threshold > 0).collect();
+        if raised.contains(&true) {
+            self.rebalance_blocks(&raised);
+        }
+    }
+
+    /// Empties the backyard as far as possible without resizing: every block that owns a
+    /// backyard entry or has a raised threshold is rebalanced, and directly spilled entries are
+    /// placed into their blocks like any other key. Returns the number of entries that still
+    /// had to be spilled because their block ran out of space.
+    pub fn drain_backyard_into_main(&mut self) -> usize {
+        if self.number_of_blocks == 0 {
+            return self.backyard.len()
+        }
+        let mut affected: Vec<bool> = self.meta_data.iter().map(|meta_data| meta_data.threshold > 0).collect();
+        for key in self.backyard.keys() {
+            affected[self.hash_block_index(key)] = true;
+        }
+        self.direct_spills.clear();
+        self.rebalance_blocks(&affected);
+        self.backyard.len()
+    }
+
+    /// Rehashes all entries into a fresh table of the given capacity in a single pass, growing or
+    /// shrinking it and dropping all offsets, raised thresholds and backyard entries that are no
+    /// longer needed. The capacity is rounded up to the block size and to the number of entries.
+    /// Like growth, the resulting placement only depends on the keys present, not on the order
+    /// they were inserted in.
+    pub fn rebuild_with_capacity(&mut self, new_capacity: impl Capacity) {
+        let capacity = self.aligned_capacity(new_capacity.capacity().max(self.len()).max(1));
+        self.resize_to(capacity);
+    }
+
+    /// Places all entries anew under the current hash builder, keeping the capacity, or raising
+    /// it to the number of entries if it is smaller
+    pub fn rehash_in_place(&mut self) {
+        self.resize_to(self.aligned_capacity(self.main_table_size.max(self.len())));
+    }
+
+    /// Replaces the hash builder, e.g. with a seeded one after a hash-flooding attack, and
+    /// rehashes all entries under it
+    pub fn set_hasher(&mut self, hash_builder: S) {
+        self.hash_builder = hash_builder;
+        self.rehash_in_place();
+    }
+
+    /// Reseeds the table automatically when it is flooded with colliding keys (off by
+    /// default): once the backyard holds more than a quarter of the entries, and at least 64,
+    /// the next insertion replaces the hash builder with `reseed` of the current one and
+    /// rehashes all entries, like `set_hasher`. If the backyard stays that full, the next check
+    /// waits until it has doubled. For the default hasher, pass `BuildFastMix::reseeded`.
+    pub fn set_reseed_on_flood(&mut self, reseed: Option<fn(&S) -> S>) {
+        self.reseed = reseed;
+    }
+
+    /// Number of times the table has reseeded its hash builder after detecting flooding
+    pub fn reseed_count(&self) -> usize {
+        self.reseed_count
+    }
+
+    /// Creates a table whose block count is a power of two, so that a key's block is just the
+    /// top bits of its hash, which is cheap to compute outside the table. The block count is
+    /// `approx / block_size` rounded to the nearest power of two (ties round up, at least 1).
+    pub fn with_capacity_pow2(approx: usize) -> Self
+    where
+        S: Default,
+    {
+        let block_size = SlickParams::default().block_size;
+        let blocks = (approx / block_size).max(1);
+        let upper = blocks.next_power_of_two();
+        let lower = upper / 2;
+        let number_of_blocks = if upper != blocks && blocks - lower < upper - blocks { lower } else { upper };
+        Self::with_capacity_and_hasher(number_of_blocks * block_size, S::default())
+    }
+
+    /// Creates a table of a capacity known at compile time, e.g. a const, which is checked to
+    /// be a whole number of blocks while compiling instead of when running. The table itself
+    /// is still allocated at runtime.
+    pub fn with_capacity_const<const CAPACITY: usize>() -> Self
+    where
+        S: Default,
+    {
+        const { assert!(CAPACITY.is_multiple_of(DEFAULT_BLOCK_SIZE), "capacity must be a multiple of the block size") };
+        Self::with_capacity_and_hasher(CAPACITY, S::default())
+    }
+
+    /// Creates a table that is `target_load` full once it holds `element_count` entries. The
+    /// capacity is rounded up to the block size, so the actual load ends up slightly lower.
+    pub fn with_target_load(element_count: usize, target_load: f64) -> Self
+    where
+        S: Default,
+    {
+        assert!(target_load > 0.0 && target_load <= 1.0, "target load must be in (0, 1]");
+        let block_size = SlickParams::default().block_size;
+        let capacity = ((element_count as f64 / target_load).ceil() as usize).max(1);
+        Self::with_capacity_and_hasher(capacity.div_ceil(block_size) * block_size, S::default())
+    }
+
+    /// Builds a table from the pairs, sized up front for `capacity` entries (rounded up to the
+    /// block size) instead of trusting the iterator's size hint. It only grows if the iterator
+    /// yields more pairs than that. Of duplicate keys, the first pair is kept.
+    pub fn from_iter_with_capacity<I: IntoIterator<Item = (Key, Value)>>(iter: I, capacity: usize) -> Self
+    where
+        S: Default,
+    {
+        let block_size = SlickParams::default().block_size;
+        let mut table = Self::with_capacity_and_hasher(capacity.div_ceil(block_size) * block_size, S::default());
+        for (key, value) in iter {
+            let _ = table.insert_pair(key, value);
+        }
+        table
+    }
+
+    /// Creates a table with the given hyperparameters, e.g. a different threshold policy
+    pub fn with_params(capacity: usize, params: SlickParams) -> Self
+    where
+        S: Default,
+    {
+        Self::with_params_and_hasher(capacity, params, S::default())
+    }
+
+    /// Creates a table whose backyard is capped softly at `limit` entries, see
+    /// `set_backyard_soft_limit`
+    pub fn with_backyard_soft_limit(capacity: usize, limit: usize) -> Self
+    where
+        S: Default,
+    {
+        let mut table = Self::with_capacity_and_hasher(capacity, S::default());
+        table.set_backyard_soft_limit(Some(limit));
+        table
+    }
+
+    /// Recommends hyperparameters for a table holding keys like the sample. Each candidate
+    /// block size (with `max_slick_size`, `max_offset` and `max_threshold` scaled like in the
+    /// default configuration) is tried by filling a table with the sample at full load and
+    /// measuring how much of it spills into the backyard.
+    /// Since larger blocks spill less but are slower to scan, the smallest block size whose
+    /// spill is within one percentage point of the least spill wins.
+    pub fn auto_tune(sample: &[Key]) -> SlickParams
+    where
+        S: Default,
+    {
+        const CANDIDATE_BLOCK_SIZES: [usize; 7] = [2, 4, 8, 10, 16, 32, 64];
+        const SPILL_TOLERANCE: f64 = 0.01;
+        if sample.is_empty() {
+            return SlickParams::default()
+        }
+        let candidates: Vec<(SlickParams, f64)> = CANDIDATE_BLOCK_SIZES
+            .iter()
+            .map(|&block_size| {
+                let params = SlickParams {
+                    block_size,
+                    max_slick_size: block_size * 2,
+                    max_offset: block_size,
+                    max_threshold: block_size,
+                    ..SlickParams::default()
+                };
+                let capacity = sample.len().div_ceil(block_size) * block_size;
+                let mut table = SlickHash::<Key, (), S>::with_params_and_hasher(capacity, params, S::default());
+                for key in sample {
+                    let _ = table.insert_pair(key.clone(), ());
+                }
+                (params, table.backyard.len() as f64 / sample.len() as f64)
+            })
+            .collect();
+        let least_spill = candidates.iter().map(|&(_, spill)| spill).fold(f64::INFINITY, f64::min);
+        candidates
+            .into_iter()
+            .find(|&(_, spill)| spill <= least_spill + SPILL_TOLERANCE)
+            .map_or_else(SlickParams::default, |(params, _)| params)
+    }
+
+    /// Like `with_params`, but returns the first violated constraint between the capacity and
+    /// the hyperparameters instead of creating a table that misbehaves or panics. Use
+    /// `SlickParams::validate` to get all violations of the hyperparameters at once.
+    pub fn try_with_params(capacity: usize, params: SlickParams) -> Result<Self, ParamError>
+    where
+        S: Default,
+    {
+        params.check(capacity)?;
+        Ok(Self::with_params(capacity, params))
+    }
+
+    /// Builds a table from pairs that are already grouped by block, in ascending block order
+    /// under the default hasher, e.g. as emitted by a previous table's `physical_layout`. The
+    /// blocks are then filled one after the other, so entries only slide into the gaps of
+    /// blocks that are still empty. The capacity is raised to hold all pairs without growing.
+    /// The order is checked in debug builds only.
+    pub fn from_block_sorted(capacity: usize, pairs: &[(Key, Value)]) -> Self
+    where
+        S: Default,
+    {
+        let block_size = SlickParams::default().block_size;
+        let capacity = capacity.max(pairs.len()).div_ceil(block_size) * block_size;
+        let mut table = Self::with_capacity_and_hasher(capacity, S::default());
+        let mut previous_block_index = 0;
+        for (key, value) in pairs {
+            let hashed = table.hash_key(key);
+            debug_assert!(hashed.block_index >= previous_block_index, "pairs are not sorted by block");
+            previous_block_index = hashed.block_index;
+            let _ = table.insert_hashed(key.clone(), hashed, value.clone());
+        }
+        table
+    }
+
+    /// The hyperparameters this table was created with, kept across resizes
+    pub fn params(&self) -> SlickParams {
+        SlickParams {
+            block_size: self.block_size,
+            max_slick_size: self.max_slick_size,
+            max_offset: self.max_offset,
+            max_threshold: self.max_threshold,
+            threshold_policy: self.threshold_policy,
+        }
+    }
+
+    /// Block a key with the given hash is assigned to, for hashes computed outside the table,
+    /// e.g. with a clone of its hash builder. Always below the number of blocks, unless the
+    /// table has no blocks at all.
+    pub fn block_for_hash(&self, hash: u64) -> usize {
+        self.block_index_from_hash(hash)
+    }
+
+    /// Threshold of a key with the given hash, in `0..max_threshold`. A key stays in its block
+    /// only while this is at least the block's threshold.
+    pub fn threshold_for_hash(&self, hash: u64) -> usize {
+        self.threshold_from_hash(hash)
+    }
+
+    /// Whether the block count is a power of two, so that a key's block is just the top bits of
+    /// its hash
+    pub fn uses_masked_hashing(&self) -> bool {
+        self.number_of_blocks.is_power_of_two()
+    }
+
+    pub fn number_of_blocks(&self) -> usize {
+        self.number_of_blocks
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Number of slots in the main table
+    pub fn capacity(&self) -> usize {
+        self.main_table_size
+    }
+
+    /// Estimated heap bytes: the main table, the block metadata and the backyard, including the
+    /// keys of directly spilled entries
+    pub fn mem_usage(&self) -> usize {
+        self.main_table.capacity() * std::mem::size_of::<(Key, Value)>()
+            + self.meta_data.capacity() * std::mem::size_of::<SlickHashMetaData>()
+            + estimated_hashmap_bytes::<Key, Value>(self.backyard.capacity())
+            + estimated_hashmap_bytes::<Key, ()>(self.direct_spills.capacity())
+    }
+
+    /// Bytes allocated for entries: every main table slot, gaps included, and every slot the
+    /// backyard has room for. Unlike `mem_usage`, block metadata and the backyard's hashing
+    /// overhead are left out, so that it compares directly with `live_bytes`.
+    pub fn capacity_bytes(&self) -> usize {
+        (self.main_table.capacity() + self.backyard.capacity()) * std::mem::size_of::<(Key, Value)>()
+    }
+
+    /// Bytes taken by the live entries, in the main table and the backyard. The difference to
+    /// `capacity_bytes` is the space lost to gaps and unused backyard slots.
+    pub fn live_bytes(&self) -> usize {
+        (self.no_elements_in_main_table + self.backyard.len()) * std::mem::size_of::<(Key, Value)>()
+    }
+
+    /// Estimated bytes this table uses beyond a std HashMap holding the same entries (negative
+    /// if it saves memory). The HashMap is modelled as grown by insertion, with a power of two
+    /// number of buckets at most 7/8 full and one control byte per bucket; both sides count
+    /// heap memory only.
+    pub fn estimated_vs_hashmap(&self) -> i64 {
+        self.mem_usage() as i64 - estimated_hashmap_bytes::<Key, Value>(self.len()) as i64
+    }
+
+    /// Fraction of main table slots holding a live entry
+    pub fn capacity_utilization(&self) -> f64 {
+        if self.main_table_size == 0 {
+            return 0.0
+        }
+        self.no_elements_in_main_table as f64 / self.main_table_size as f64
+    }
+
+    /// Fraction of all entries that live in the backyard
+    pub fn backyard_ratio(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0
+        }
+        self.backyard.len() as f64 / self.len() as f64
+    }
+
+    /// Estimated number of further insertions the main table can take before entries spill into
+    /// the backyard: the free slots of every block, counting no block beyond `max_slick_size`
+    /// entries. This is an upper bound for evenly spread keys; since keys hash to blocks
+    /// unevenly, spilling usually starts somewhat earlier.
+    pub fn capacity_remaining_before_spill(&self) -> usize {
+        (0..self.number_of_blocks)
+            .map(|block_index| {
+                let live_len = self.block_range(block_index).len();
+                self.meta_data[block_index].gap.min(self.max_slick_size.saturating_sub(live_len))
+            })
+            .sum()
+    }
+
+    /// How evenly the entries are spread over the blocks they hash to, counting backyard
+    /// entries towards their block: 1 minus the coefficient of variation of the block loads,
+    /// normalized by its maximum, the one of all entries in a single block. 1 is perfectly even,
+    /// 0 is as skewed as possible.
+    pub fn block_load_balance_score(&self) -> f64 {
+        if self.number_of_blocks < 2 || self.is_empty() {
+            return 1.0
+        }
+        let mut loads: Vec<usize> = (0..self.number_of_blocks)
+            .map(|block_index| self.block_range(block_index).len())
+            .collect();
+        for key in self.backyard.keys() {
+            loads[self.hash_block_index(key)] += 1;
+        }
+        let mean = self.len() as f64 / self.number_of_blocks as f64;
+        let variance = loads.iter()
+            .map(|&load| (load as f64 - mean).powi(2))
+            .sum::<f64>() / self.number_of_blocks as f64;
+        let max_coefficient_of_variation = ((self.number_of_blocks - 1) as f64).sqrt();
+        (1.0 - variance.sqrt() / mean / max_coefficient_of_variation).clamp(0.0, 1.0)
+    }
+
+    /// Number of blocks at each threshold: index t counts the blocks whose threshold is t,
+    /// for t in 0..=max_threshold. Mass away from 0 means blocks have bumped entries into the
+    /// backyard, the further right the more of them.
+    pub fn threshold_histogram(&self) -> Vec<usize> {
+        let mut histogram = vec![0; self.max_threshold + 1];
+        for meta_data in &self.meta_data {
+            // A custom threshold policy may raise a threshold beyond max_threshold
+            if meta_data.threshold >= histogram.len() {
+                histogram.resize(meta_data.threshold + 1, 0);
+            }
+            histogram[meta_data.threshold] += 1;
+        }
+        histogram
+    }
+
+    /// Whether the table has fragmented enough that rebuilding it, e.g. with
+    /// `rebuild_with_capacity` or `rehash_in_place`, is worthwhile: a large share of the
+    /// entries lives in the backyard, some block is shifted as far as `max_offset` so that
+    /// sliding towards it stops working, or blocks are shifted by more than half of
+    /// `max_offset` on average. Always false if nothing happened since the last rebuild.
+    pub fn needs_compaction(&self) -> bool {
+        if self.insertions_since_compaction + self.removals_since_compaction == 0 || self.number_of_blocks == 0 {
+            return false
+        }
+        let total_offset: usize = self.meta_data.iter().map(|meta_data| meta_data.offset).sum();
+        let max_offset_reached = self.meta_data.iter().any(|meta_data| meta_data.offset >= self.max_offset);
+        let mean_offset_ratio = total_offset as f64 / (self.number_of_blocks * self.max_offset) as f64;
+        self.backyard_ratio() > COMPACTION_BACKYARD_RATIO
+            || max_offset_reached
+            || mean_offset_ratio > COMPACTION_MEAN_OFFSET_RATIO
+    }
+
+    /// Number of insertions, including those of present keys, since the main table was last
+    /// rebuilt by growing, shrinking or an explicit rebuild
+    pub fn insertions_since_compaction(&self) -> usize {
+        self.insertions_since_compaction
+    }
+
+    /// Number of removed entries since the main table was last rebuilt
+    pub fn removals_since_compaction(&self) -> usize {
+        self.removals_since_compaction
+    }
+
+    /// Looks up the key and also returns its hash under the table's hash builder, which picks
+    /// the key's block, so that callers keyed by the same hash don't have to compute it again
+    pub fn get_with_hash(&self, key: &Key) -> (u64, Option<&Value>) {
+        let hashed = self.hash_key(key);
+        (hashed.hash, self.get_hashed(key, hashed))
+    }
+
+    /// Looks up every key, returning the values in the order of the keys. The keys are hashed
+    /// up front and probed block by block, so that neighbouring blocks are visited together.
+    pub fn get_batch(&self, keys: &[Key]) -> Vec<Option<&Value>> {
+        let mut hashed_keys: Vec<(usize, HashedKey)> = keys.iter().map(|key| self.hash_key(key)).enumerate().collect();
+        hashed_keys.sort_unstable_by_key(|(_, hashed)| hashed.block_index);
+        let mut values = vec![None; keys.len()];
+        for (position, hashed) in hashed_keys {
+            values[position] = self.get_hashed(&keys[position], hashed);
+        }
+        values
+    }
+
+    /// Like `get_batch`, but hashes the keys in small groups and prefetches the metadata and
+    /// nominal start of each key's block before probing any of them, hiding memory latency on
+    /// tables much larger than the CPU caches
+    #[cfg(feature = "prefetch")]
+    pub fn get_batch_prefetched(&self, keys: &[Key]) -> Vec<Option<&Value>> {
+        let mut values = Vec::with_capacity(keys.len());
+        for key_group in keys.chunks(PREFETCH_BATCH) {
+            let hashed_keys: Vec<HashedKey> = key_group.iter().map(|key| self.hash_key(key)).collect();
+            if self.number_of_blocks > 0 {
+                for hashed in &hashed_keys {
+                    prefetch(&self.meta_data[hashed.block_index]);
+                    prefetch(&self.main_table[self.block_size * hashed.block_index]);
+                }
+            }
+            values.extend(key_group.iter().zip(hashed_keys).map(|(key, hashed)| self.get_hashed(key, hashed)));
+        }
+        values
+    }
+
+    /// Mutable access to the values of two distinct keys at once, e.g. to swap them.
+    /// Returns None if the keys are equal or either of them is missing.
+    pub fn get2_mut(&mut self, a: &Key, b: &Key) -> Option<(&mut Value, &mut Value)> {
+        if a == b {
+            return None
+        }
+        match (self.locate(a)?, self.locate(b)?) {
+            (Location::MainTable(a_index), Location::MainTable(b_index)) => {
+                let (low, high) = (a_index.min(b_index), a_index.max(b_index));
+                let (left, right) = self.main_table.split_at_mut(high);
+                let (low_value, high_value) = (&mut left[low].1, &mut right[0].1);
+                if a_index < b_index {
+                    Some((low_value, high_value))
+                } else {
+                    Some((high_value, low_value))
+                }
+            }
+            (Location::MainTable(a_index), Location::Backyard) => {
+                Some((&mut self.main_table[a_index].1, self.backyard.get_mut(b)?))
+            }
+            (Location::Backyard, Location::MainTable(b_index)) => {
+                Some((self.backyard.get_mut(a)?, &mut self.main_table[b_index].1))
+            }
+            (Location::Backyard, Location::Backyard) => {
+                let [a_value, b_value] = self.backyard.get_disjoint_mut([a, b]);
+                Some((a_value?, b_value?))
+            }
+        }
+    }
+
+    /// Mutable access to the values of any number of distinct keys at once, in the order of
+    /// the keys, e.g. for batch updates. Returns None if a key is given twice or is missing.
+    pub fn get_disjoint_mut(&mut self, keys: &[&Key]) -> Option<Vec<&mut Value>> {
+        let mut distinct_keys = HashMap::with_capacity(keys.len());
+        for (position, &key) in keys.iter().enumerate() {
+            if distinct_keys.insert(key, position).is_some() {
+                return None
+            }
+        }
+        let mut main_table_indices = Vec::with_capacity(keys.len());
+        let mut any_in_backyard = false;
+        for (position, &key) in keys.iter().enumerate() {
+            match self.locate(key)? {
+                Location::MainTable(index) => main_table_indices.push((index, position)),
+                Location::Backyard => any_in_backyard = true,
+            }
+        }
+
+        let mut values: Vec<Option<&mut Value>> = (0..keys.len()).map(|_| None).collect();
+        // Splits the value of each slot off the front of the rest of the main table, slots
+        // ascending, which is possible since distinct keys occupy distinct slots
+        main_table_indices.sort_unstable();
+        let mut rest = self.main_table.as_mut_slice();
+        let mut rest_start = 0;
+        for (index, position) in main_table_indices {
+            let (slot, tail) = std::mem::take(&mut rest)[index - rest_start..].split_first_mut().expect("located slot");
+            values[position] = Some(&mut slot.1);
+            rest = tail;
+            rest_start = index + 1;
+        }
+        if any_in_backyard {
+            for (key, value) in self.backyard.iter_mut() {
+                if let Some(&position) = distinct_keys.get(key) {
+                    values[position] = Some(value);
+                }
+            }
+        }
+        values.into_iter().collect()
+    }
+
+    /// Swaps the values of two keys, wherever each of them lives. Returns false, changing
+    /// nothing, if either key is absent. Swapping a key with itself is a no-op.
+    pub fn swap_values(&mut self, a: &Key, b: &Key) -> bool {
+        if a == b {
+            return self.locate(a).is_some()
+        }
+        match self.get2_mut(a, b) {
+            Some((a_value, b_value)) => {
+                std::mem::swap(a_value, b_value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes an entry chosen uniformly at random, e.g. to evict from a cache, or returns None
+    /// if the table is empty. The main table and the backyard are picked by their share of the
+    /// entries. Within the main table, random slots are drawn until one holds a live entry,
+    /// which takes few draws unless the table is nearly empty. A backyard entry is found by
+    /// walking the backyard up to a random position, so picking one costs O(backyard) time.
+    #[cfg(feature = "rand")]
+    pub fn remove_random(&mut self, rng: &mut impl rand::Rng) -> Option<(Key, Value)> {
+        if self.is_empty() {
+            return None
+        }
+        let key = if rng.gen_range(0..self.len()) < self.no_elements_in_main_table {
+            loop {
+                let slot = rng.gen_range(0..self.main_table_size);
+                // Only the slot's nominal block and the left neighbour, which may reach into it,
+                // can hold the slot
+                let block_index = slot / self.block_size;
+                let is_live = self.block_range(block_index).contains(&slot)
+                    || (block_index > 0 && self.block_range(block_index - 1).contains(&slot));
+                if is_live {
+                    break self.main_table[slot].0.clone()
+                }
+            }
+        } else {
+            let backyard_index = rng.gen_range(0..self.backyard.len());
+            self.backyard.keys().nth(backyard_index).expect("index within the backyard").clone()
+        };
+        HashTableRemove::remove_entry(self, &key)
+    }
+
+    /// Places the pair straight into the backyard, bypassing its block. This is meant for keys
+    /// known to be hot, whose insertion would otherwise bump many block neighbours. If the key
+    /// is already present, its current entry is returned wherever it lives.
+    ///
+    /// Placement policy: keys below their block's threshold are looked up in the backyard only,
+    /// all others in their block. While directly spilled entries exist, a miss in the block
+    /// falls back to the backyard, so get, entry and remove find them like any other entry.
+    /// The fallback ends once the last of them has left the backyard.
+    ///
+    /// Like any insertion of a new key, this grows the table first once the main table could
+    /// hold every entry, so the table never holds more entries than its capacity.
+    pub fn insert_into_backyard_pub(&mut self, key: Key, value: Value) -> Insertion<'_, Value> {
+        match self.locate(&key) {
+            Some(Location::MainTable(index)) => Insertion::Occupied(&mut self.main_table[index].1),
+            Some(Location::Backyard) => self.insert_into_backyard(key, value),
+            None => self.spill_directly(key, value),
+        }
+    }
+
+    // Places an absent key into the backyard on purpose. Like any other insertion, it grows
+    // the table first once the main table could hold every entry, which keeps the number of
+    // entries within the capacity.
+    fn spill_directly(&mut self, key: Key, value: Value) -> Insertion<'_, Value> {
+        if self.len() >= self.main_table_size {
+            self.resize_to(self.grow_hint());
+        }
+        self.insertions_since_compaction += 1;
+        self.direct_spills.insert(key.clone());
+        self.insert_into_backyard(key, value)
+    }
+
+    /// Mutable access to the key's value, inserting `default` if it is absent. A new key whose
+    /// block holds `max_slick_size - 1` entries or more is placed straight into the backyard,
+    /// like with `insert_into_backyard_pub`, instead of filling the block up to the point where
+    /// the next insertion bumps entries. All other new keys are inserted as usual.
+    pub fn get_mut_or_spill(&mut self, key: Key, default: Value) -> &mut Value {
+        let mut hashed = self.hash_key(&key);
+        match self.locate_hashed(&key, hashed) {
+            Some(Location::MainTable(index)) => return &mut self.main_table[index].1,
+            Some(Location::Backyard) => return self.backyard.get_mut(&key).expect("entry located in the backyard"),
+            None => {}
+        }
+        // Grows first like any other insertion, so that the block is judged in the table the
+        // key ends up in
+        if self.len() >= self.main_table_size {
+            self.resize_to(self.grow_hint());
+            hashed = self.hashed_from(hashed.hash);
+        }
+        let block_nearly_full = self.number_of_blocks > 0
+            && hashed.threshold >= self.meta_data[hashed.block_index].threshold
+            && self.block_range(hashed.block_index).len() + 1 >= self.max_slick_size;
+        if block_nearly_full {
+            return self.spill_directly(key, default).into_mut()
+        }
+        self.insert_absent(key, hashed, default).into_mut()
+    }
+
+    /// Dry run of inserting the key: the number of entries its insertion would bump from its
+    /// block into the backyard right now. Nothing is modified. Returns 0 if the insertion
+    /// would grow the table instead, because it is full or the backyard has reached its soft
+    /// limit, since growing rehashes all entries.
+    pub fn count_bumped_for(&self, key: &Key) -> usize {
+        let hashed = self.hash_key(key);
+        if self.number_of_blocks == 0 || self.needs_growth_for(hashed) || self.locate_hashed(key, hashed).is_some() {
+            return 0
+        }
+        let block_index = hashed.block_index;
+        let key_threshold = hashed.threshold;
+        if key_threshold < self.meta_data[block_index].threshold {
+            return 0
+        }
+        let block_range = self.block_range(block_index);
+        let there_is_space = block_range.len() < self.max_slick_size && (
+            self.meta_data[block_index].gap > 0 ||
+                self.find_gap_on_left(block_index).is_some() ||
+                self.find_gap_on_right(block_index).is_some()
+        );
+        if there_is_space {
+            return 0
+        }
+        let t_prime = self.bump_threshold(&block_range, key_threshold);
+        self.main_table[block_range]
+            .iter()
+            .filter(|(iter_key, _)| self.hash_threshold(iter_key) < t_prime)
+            .count()
+    }
+
+    /// Number of entries in the main table and in the backyard, in that order
+    pub fn entry_count_by_location(&self) -> (usize, usize) {
+        (self.no_elements_in_main_table, self.backyard.len())
+    }
+
+    /// The block the key hashes to, or None for a table without blocks
+    pub fn block_index_of(&self, key: &Key) -> Option<usize> {
+        (self.number_of_blocks > 0).then(|| self.hash_block_index(key))
+    }
+
+    /// The keys stored in the block, e.g. to find out which keys collide in a full block. Keys
+    /// of the block that were bumped into the backyard are not included. Empty for an
+    /// out-of-range block index.
+    pub fn collision_keys_in_block(&self, block_index: usize) -> Vec<&Key> {
+        let block_range = self.block_range_checked(block_index).unwrap_or_default();
+        self.main_table[block_range].iter().map(|(key, _)| key).collect()
+    }
+
+    /// Whether the key is stored in its main table block, regardless of the block's threshold
+    pub fn contains_in_main_table(&self, key: &Key) -> bool {
+        if self.number_of_blocks == 0 {
+            return false
+        }
+        let block_range = self.block_range(self.hash_block_index(key));
+        self.main_table[block_range].iter().any(|(iter_key, _)| iter_key == key)
+    }
+
+    /// Whether the key is stored in the backyard, regardless of its block's threshold
+    pub fn contains_in_backyard(&self, key: &Key) -> bool {
+        self.backyard.contains_key(key)
+    }
+
+    /// Swaps in a new value for a present key and returns the old one. Absent keys are not
+    /// inserted; None is returned instead.
+    pub fn replace_value(&mut self, key: &Key, value: Value) -> Option<Value> {
+        match self.locate(key)? {
+            Location::MainTable(index) => Some(std::mem::replace(&mut self.main_table[index].1, value)),
+            Location::Backyard => self.backyard.get_mut(key).map(|old| std::mem::replace(old, value)),
+        }
+    }
+
+    /// Overwrites the value of a present key, borrowing the key instead of taking an owned
+    /// one, so that e.g. updating a `String` key's entry allocates nothing. Returns false, and
+    /// drops the value, if the key is absent.
+    pub fn update_value(&mut self, key: &Key, value: Value) -> bool {
+        self.replace_value(key, value).is_some()
+    }
+
+    /// Inserts the pair only if it fits into its main table block without spilling into the
+    /// backyard, bumping other entries or growing the table. Returns None otherwise, leaving
+    /// the table's contents unchanged.
+    pub fn try_insert_strict(&mut self, key_value_pair: (Key, Value)) -> Option<Insertion<'_, Value>> {
+        let (key, value) = key_value_pair;
+        let hashed = self.hash_key(&key);
+        if let Some(location) = self.locate_hashed(&key, hashed) {
+            self.insertions_since_compaction += 1;
+            return match location {
+                Location::MainTable(index) => Some(Insertion::Occupied(&mut self.main_table[index].1)),
+                Location::Backyard => self.backyard.get_mut(&key).map(Insertion::Occupied),
+            }
+        }
+        if self.number_of_blocks == 0 {
+            return None
+        }
+        let block_index = hashed.block_index;
+        let block_range = self.block_range(block_index);
+        if hashed.threshold < self.meta_data[block_index].threshold
+            || self.there_is_no_space(&block_range, block_index) {
+            return None
+        }
+        self.insertions_since_compaction += 1;
+        Some(self.insert_at_block_end(block_index, key, value))
+    }
+
+    /// Inserts all pairs and reports for each one whether it lives in the main table (true) or
+    /// has spilled into the backyard (false). Placement is reported after the whole batch,
+    /// since inserting a later pair may bump an earlier one.
+    pub fn bulk_insert_with_feedback(&mut self, pairs: &[(Key, Value)]) -> Vec<bool> {
+        for (key, value) in pairs {
+            let _ = self.insert_pair(key.clone(), value.clone());
+        }
+        pairs.iter()
+            .map(|(key, _)| matches!(self.locate(key), Some(Location::MainTable(_))))
+            .collect()
+    }
+
+    /// Strictly inserts all pairs, or none of them: if one of them can't be inserted without
+    /// spilling, the pairs inserted so far are removed again and the index of the failing pair
+    /// is returned
+    pub fn insert_all_or_nothing(&mut self, pairs: &[(Key, Value)]) -> Result<(), usize> {
+        let mut inserted = Vec::new();
+        let insertions_since_compaction = self.insertions_since_compaction;
+        for (index, (key, value)) in pairs.iter().enumerate() {
+            match self.try_insert_strict((key.clone(), value.clone())) {
+                Some(insertion) => {
+                    if insertion.is_inserted() {
+                        inserted.push(index);
+                    }
+                }
+                None => {
+                    // Undone newest first, so that every block ends up with its entries in
+                    // their former slots. Neither the insertions nor their undoing count as
+                    // operations on the table.
+                    for inserted_index in inserted.into_iter().rev() {
+                        self.take_entry(&pairs[inserted_index].0);
+                    }
+                    self.insertions_since_compaction = insertions_since_compaction;
+                    return Err(index)
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Keeps only the entries for which `f` returns true. Unless disabled through
+    /// `set_rebalance_on_bulk_remove`, the blocks that lost entries are rebalanced afterwards.
+    pub fn retain<F: FnMut(&Key, &Value) -> bool>(&mut self, mut f: F) {
+        self.retain_mut(|key, value| f(key, value));
+    }
+
+    /// Like `retain`, but `f` may also modify the values, e.g. to update the entries it keeps
+    /// in the same pass. Values of removed entries may be modified as well before they drop.
+    pub fn retain_mut<F: FnMut(&Key, &mut Value) -> bool>(&mut self, mut f: F) {
+        let mut affected = vec![false; self.number_of_blocks];
+        for (block_index, block_affected) in affected.iter_mut().enumerate() {
+            let mut index = self.block_start(block_index);
+            let mut block_end = self.block_end(block_index);
+            while index < block_end {
+                let (key, value) = &mut self.main_table[index];
+                if f(key, value) {
+                    index += 1;
+                } else {
+                    // Fills the hole with the block's last entry
+                    self.main_table.swap(index, block_end-1);
+                    self.meta_data[block_index].gap += 1;
+                    #[cfg(debug_assertions)]
+                    { self.meta_data[block_index].live_len -= 1; }
+                    self.no_elements_in_main_table -= 1;
+                    self.removals_since_compaction += 1;
+                    block_end -= 1;
+                    *block_affected = true;
+                }
+            }
+            self.debug_assert_live_len(block_index);
+        }
+        let backyard_len = self.backyard.len();
+        self.backyard.retain(|key, value| f(key, value));
+        self.removals_since_compaction += backyard_len - self.backyard.len();
+        if !self.direct_spills.is_empty() {
+            self.direct_spills.retain(|key| self.backyard.contains_key(key));
+        }
+        if self.rebalance_on_bulk_remove && affected.contains(&true) {
+            self.rebalance_blocks(&affected);
+        }
+    }
+
+    /// Moves the entries for which `f` returns true into a new table with the same
+    /// hyperparameters and hasher, sized to hold them
+    pub fn split_off<F: Fn(&Key, &Value) -> bool>(&mut self, f: F) -> Self {
+        let mut split_entries = Vec::new();
+        self.retain(|key, value| {
+            if f(key, value) {
+                split_entries.push((key.clone(), value.clone()));
+                return false
+            }
+            true
+        });
+        let mut split = Self::with_params_and_hasher(0, self.params(), self.hash_builder.clone());
+        split.rebalance_on_bulk_remove = self.rebalance_on_bulk_remove;
+        split.refill_gapless_left_on_remove = self.refill_gapless_left_on_remove;
+        split.reseed = self.reseed;
+        split.reserve(split_entries.len());
+        for (key, value) in split_entries {
+            let _ = split.insert_pair(key, value);
+        }
+        split.backyard_soft_limit = self.backyard_soft_limit;
+        split
+    }
+
+    /// Moves all entries of `other` into this table. A key present in both ends up with
+    /// `f(self_value, other_value)`, e.g. `|a, b| a + b` to combine counters of two partitions.
+    pub fn merge_with<F: Fn(&Value, &Value) -> Value>(&mut self, other: Self, f: F) {
+        for (key, value) in other.into_entries() {
+            match self.entry(key) {
+                Entry::Occupied(mut occupied) => {
+                    let merged = f(occupied.get(), &value);
+                    occupied.insert(merged);
+                }
+                Entry::Vacant(vacant) => {
+                    vacant.insert(value);
+                }
+            }
+        }
+    }
+
+    /// Removes all given keys and returns how many were present. The keys are grouped by
+    /// block, and each block is compacted in a single pass that removes all of its keys at
+    /// once, instead of closing one hole per key.
+    pub fn bulk_remove_sorted(&mut self, keys: &[Key]) -> usize {
+        if self.number_of_blocks == 0 {
+            return keys.iter().filter(|key| self.remove_entry(key).is_some()).count()
+        }
+        let mut keys_by_block: Vec<(HashedKey, &Key)> = keys.iter().map(|key| (self.hash_key(key), key)).collect();
+        keys_by_block.sort_unstable_by_key(|&(hashed, _)| hashed.block_index);
+        let mut removed = 0;
+        for block_keys in keys_by_block.chunk_by(|(a, _), (b, _)| a.block_index == b.block_index) {
+            let block_index = block_keys[0].0.block_index;
+            let threshold = self.meta_data[block_index].threshold;
+            // Keys below the block's threshold can only live in the backyard
+            let (backyard_keys, block_keys): (Vec<_>, Vec<_>) = block_keys
+                .iter()
+                .map(|&(hashed, key)| (hashed.threshold < threshold, key))
+                .partition(|&(in_backyard, _)| in_backyard);
+            for (_, key) in backyard_keys {
+                if self.remove_from_backyard(key).is_some() {
+                    removed += 1;
+                }
+            }
+            let block_keys: Vec<&Key> = block_keys.into_iter().map(|(_, key)| key).collect();
+            let removed_from_block = self.remove_from_block(block_index, &block_keys);
+            removed += removed_from_block.len();
+            if !self.direct_spills.is_empty() {
+                for key in block_keys.into_iter().filter(|key| !removed_from_block.contains(key)) {
+                    if self.remove_from_backyard(key).is_some() {
+                        removed += 1;
+                    }
+                }
+            }
+        }
+        self.removals_since_compaction += removed;
+        removed
+    }
+
+    // Removes the given keys from the block in a single pass: survivors are moved to the front
+    // and the removed entries end up in the gap, which is enlarged once. Returns the keys that
+    // were found.
+    fn remove_from_block(&mut self, block_index: usize, keys: &[&Key]) -> Vec<Key> {
+        let block_range = self.block_range(block_index);
+        let mut kept = block_range.start;
+        for index in block_range.clone() {
+            if !keys.contains(&&self.main_table[index].0) {
+                self.main_table.swap(kept, index);
+                kept += 1;
+            }
+        }
+        let removed_len = block_range.end - kept;
+        if removed_len == 0 {
+            return Vec::new()
+        }
+        let removed_keys = self.main_table[kept..block_range.end].iter().map(|(key, _)| key.clone()).collect();
+        self.meta_data[block_index].gap += removed_len;
+        #[cfg(debug_assertions)]
+        { self.meta_data[block_index].live_len -= removed_len; }
+        self.no_elements_in_main_table -= removed_len;
+        self.reconcile_offset(block_index + 1);
+        if self.refill_gapless_left_on_remove {
+            self.refill_gapless_left_neighbour(block_index);
+        }
+        self.debug_assert_live_len(block_index);
+        removed_keys
+    }
+
+    /// Whether bulk removals like `retain` rebalance the affected blocks afterwards (the default).
+    /// Disabling it makes them cheaper but leaves offsets and raised thresholds behind.
+    pub fn set_rebalance_on_bulk_remove(&mut self, enabled: bool) {
+        self.rebalance_on_bulk_remove = enabled;
+    }
+
+    /// Whether a single removal also hands a freed slot to a left neighbour without any gap
+    /// (off by default). Slots are always handed back to a right neighbour that was shifted to
+    /// make room. The left neighbour isn't tracked the same way: one without gap is only likely
+    /// to have lent its last slot in a slide from the left, so this may also move a slot to a
+    /// block that simply filled up.
+    pub fn set_refill_gapless_left_on_remove(&mut self, enabled: bool) {
+        self.refill_gapless_left_on_remove = enabled;
+    }
+
+    /// Caps the backyard softly: once it holds `limit` entries, an insertion that would put a
+    /// new key or bumped keys into it grows the table first. Growth happens at most once per
+    /// insertion, and a bump may still move several keys at once, so the backyard can end up
+    /// somewhat above the limit. If a resize leaves the backyard at the limit, as keys that
+    /// collide a lot may, the limit rests until the table is resized for another reason,
+    /// instead of doubling it on every spill. `None` (the default) lets the backyard grow freely.
+    pub fn set_backyard_soft_limit(&mut self, limit: Option<usize>) {
+        self.backyard_soft_limit = limit;
+        self.backyard_limit_suspended = false;
+    }
+
+    /// Changes how far blocks without space raise their threshold from now on. Thresholds that
+    /// are already raised stay as they are.
+    pub fn set_threshold_policy(&mut self, policy: ThresholdPolicy) {
+        self.threshold_policy = policy;
+    }
+
+    /// Defragments a single block. Its live entries always sit contiguously from block_start
+    /// with the gap at the tail, so this normalizes the block's offset: it moves back towards
+    /// its nominal start using spare slots of the left neighbour, and spare slots of its own
+    /// are handed back to the right neighbour if that one is shifted. Entries never change
+    /// their block and the neighbours stay valid.
+    pub fn defragment_block(&mut self, block_index: usize) {
+        assert!(block_index < self.number_of_blocks);
+        self.reconcile_offset(block_index);
+        self.reconcile_offset(block_index + 1);
+    }
+
+    /// Moves every block back towards its nominal start as far as the spare slots of its left
+    /// neighbour allow. Going left to right, each shift hands a slot to the next block, so a
+    /// single pass restores the sliding headroom left behind by removals. Entries never change
+    /// their block.
+    pub fn rebalance_offsets(&mut self) {
+        for block_index in 1..self.number_of_blocks {
+            self.reconcile_offset(block_index);
+        }
+    }
+
+    /// Escape hatch for tables whose backyard has absorbed most entries: converts into a plain
+    /// HashMap once `backyard_ratio()` reaches `threshold`, otherwise hands the table back
+    #[allow(clippy::result_large_err)]
+    pub fn into_hashmap_if_degraded(self, threshold: f64) -> Result<HashMap<Key, Value>, Self> {
+        if self.backyard_ratio() < threshold {
+            return Err(self)
+        }
+        Ok(self.into_entries().into_iter().collect())
+    }
+
+    /// Every main table slot in physical order, each block's entries followed by its gap slots.
+    /// Unlike a logical iteration this includes the gaps and leaves out the backyard.
+    pub fn physical_layout(&self) -> impl Iterator<Item = Slot<&Key, &Value>> + '_ {
+        (0..self.number_of_blocks).flat_map(move |block_index| {
+            let gap = self.meta_data[block_index].gap;
+            self.main_table[self.block_range(block_index)]
+                .iter()
+                .map(|(key, value)| Slot::Occupied(key, value))
+                .chain((0..gap).map(|_| Slot::Gap))
+        })
+    }
+
+    /// Some entry of the table: the first one of the first non-empty block, or a backyard entry
+    /// if the main table is empty. Takes at most one step per block.
+    pub fn any_entry(&self) -> Option<(&Key, &Value)> {
+        (0..self.number_of_blocks)
+            .map(|block_index| self.block_range(block_index))
+            .find(|block_range| !block_range.is_empty())
+            .map(|block_range| {
+                let (key, value) = &self.main_table[block_range.start];
+                (key, value)
+            })
+            .or_else(|| self.backyard.iter().next())
+    }
+
+    /// Removes and returns some entry, taking it from the backyard while it isn't empty. Main
+    /// table entries are taken from the end of a block, so that nothing has to be moved.
+    pub fn pop(&mut self) -> Option<(Key, Value)> {
+        let key = match self.backyard.keys().next() {
+            Some(key) => key.clone(),
+            None => {
+                let block_range = (0..self.number_of_blocks)
+                    .map(|block_index| self.block_range(block_index))
+                    .find(|block_range| !block_range.is_empty())?;
+                self.main_table[block_range.end - 1].0.clone()
+            }
+        };
+        self.remove_entry(&key)
+    }
+
+    // Panics unless get finds every stored entry exactly where it is, i.e. insertion never
+    // placed a key where lookups don't search for it, and every directly spilled key is still
+    // in the backyard
+    #[cfg(test)]
+    fn assert_routing_consistency(&self) {
+        for block_index in 0..self.number_of_blocks {
+            for index in self.block_range(block_index) {
+                let (key, value) = &self.main_table[index];
+                let found = self.get_hashed(key, self.hash_key(key));
+                assert!(found.is_some_and(|found| std::ptr::eq(found, value)),
+                        "entry in slot {index} of block {block_index} is not found by get");
+            }
+        }
+        for (key, value) in &self.backyard {
+            let found = self.get_hashed(key, self.hash_key(key));
+            assert!(found.is_some_and(|found| std::ptr::eq(found, value)),
+                    "backyard entry is not found by get");
+        }
+        assert!(self.direct_spills.iter().all(|key| self.backyard.contains_key(key)),
+                "a directly spilled key has left the backyard without being forgotten");
+    }
+
+    /// Summary of every block, in block order
+    pub fn iter_blocks(&self) -> impl Iterator<Item = BlockSummary> + '_ {
+        (0..self.number_of_blocks).map(move |block_index| {
+            let block_range = self.block_range(block_index);
+            let meta_data = &self.meta_data[block_index];
+            BlockSummary {
+                index: block_index,
+                start: block_range.start,
+                end: block_range.end,
+                live_len: block_range.len(),
+                offset: meta_data.offset,
+                gap: meta_data.gap,
+                threshold: meta_data.threshold,
+            }
+        })
+    }
+
+    /// Indices of the blocks holding fewer than `fraction * block_size` live entries, in block
+    /// order, e.g. to pick the blocks worth passing to `defragment_block`
+    pub fn blocks_below_utilization(&self, fraction: f64) -> Vec<usize> {
+        let limit = fraction * self.block_size as f64;
+        (0..self.number_of_blocks)
+            .filter(|&block_index| (self.block_range(block_index).len() as f64) < limit)
+            .collect()
+    }
+
+    /// Consumes the table and returns all entries sorted by key, independent of the internal layout
+    pub fn into_sorted_vec(self) -> Vec<(Key, Value)>
+    where
+        Key: Ord,
+    {
+        let mut entries = self.into_entries();
+        entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        entries
+    }
+
+    /// All keys in ascending order, independent of the internal layout
+    pub fn keys_sorted(&self) -> Vec<&Key>
+    where
+        Key: Ord,
+    {
+        let mut keys: Vec<&Key> = self.entries().map(|(key, _)| key).collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// All entries in ascending key order, independent of the internal layout
+    pub fn entries_sorted(&self) -> Vec<(&Key, &Value)>
+    where
+        Key: Ord,
+    {
+        let mut entries: Vec<(&Key, &Value)> = self.entries().collect();
+        entries.sort_unstable_by_key(|&(key, _)| key);
+        entries
+    }
+}
+
+impl<Key, Value, S> HashTableBase<Key, Value> for SlickHash<Key, Value, S>
+where
+    Key: Clone + Eq + PartialEq + Hash + Default,
+    Value: Clone + Default,
+    S: BuildHasher + Clone + Default,
+{
+    // A few blocks, so that the first insertions don't grow the table one block at a time
+    const DEFAULT_CAPACITY: usize = 16 * DEFAULT_BLOCK_SIZE;
+
+    // Rounded up to a whole number of blocks, never below the requested capacity
+    fn with_capacity(capacity: impl Capacity) -> Self {
+        let block_size = SlickParams::default().block_size;
+        let capacity = capacity.capacity().div_ceil(block_size) * block_size;
+        SlickHash::with_capacity_and_hasher(capacity, S::default())
+    }
+
+    fn try_insert(&mut self, key_value_pair: (Key, Value)) -> Insertion<'_, Value> {
+        let (key, value) = key_value_pair;
+        self.insert_pair(key, value)
+    }
+
+    fn insert_owned(&mut self, key: Key, value: Value) -> Option<Value> {
+        let hashed = self.hash_key(&key);
+        match self.locate_hashed(&key, hashed) {
+            Some(Location::MainTable(index)) => Some(std::mem::replace(&mut self.main_table[index].1, value)),
+            Some(Location::Backyard) => self.backyard.insert(key, value),
+            None => {
+                let _ = self.insert_absent(key, hashed, value);
+                None
+            }
+        }
+    }
+
+    fn get(&self, key: &Key) -> Option<&Value> {
+        self.get_hashed(key, self.hash_key(key))
+    }
+
+    // Probes the keys in block order, so that each block is visited while it is cache-resident
+    fn bulk_contains(&self, keys: &[Key]) -> usize {
+        let mut hashed_keys: Vec<(HashedKey, &Key)> = keys.iter().map(|key| (self.hash_key(key), key)).collect();
+        hashed_keys.sort_unstable_by_key(|(hashed, _)| hashed.block_index);
+        hashed_keys.into_iter()
+            .filter(|&(hashed, key)| self.get_hashed(key, hashed).is_some())
+            .count()
+    }
+
+    fn get_batch<'a>(&'a self, keys: &[Key]) -> Vec<Option<&'a Value>> {
+        self.get_batch(keys)
+    }
+
+    fn get_mut(&mut self, key: &Key) -> Option<&mut Value> {
+        match self.locate(key)? {
+            Location::MainTable(index) => Some(&mut self.main_table[index].1),
+            Location::Backyard => self.backyard.get_mut(key),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.reserve(additional)
+    }
 }
 
-impl<Key, Value> HashTableBulk<Key, Value> for SlickHash<Key, Value> {
+impl<Key, Value, S> HashTableBulk<Key, Value> for SlickHash<Key, Value, S>
+where
+    Key: Clone + Eq + PartialEq + Hash + Default,
+    Value: Clone + Default,
+    S: BuildHasher + Clone,
+{
     fn bulk_insert(&mut self, key_value_pairs: &[(Key, Value)]) {
-        todo!()
+        for (key, value) in key_value_pairs {
+            let _ = self.insert_pair(key.clone(), value.clone());
+        }
     }
 }
 
-impl<Key, Value> HashTableRemove<Key, Value> for SlickHash<Key, Value>
+// Like bulk_insert, keeps the present value of a key that is already in the table
+impl<Key, Value, S> Extend<(Key, Value)> for SlickHash<Key, Value, S>
 where
     Key: Clone + Eq + PartialEq + Hash + Default,
     Value: Clone + Default,
+    S: BuildHasher + Clone,
 {
-    fn remove_entry(&mut self, key: &Key) -> Option<(Key, Value)> {
-        let block_index = self.hash_block_index(key);
-        let mut remove_value = None;
-        if self.hash_threshold(key) < self.meta_data[block_index].threshold {
-            remove_value = self.backyard.remove_entry(key)
+    fn extend<I: IntoIterator<Item = (Key, Value)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        // Grows once up front for the pairs the iterator is sure to yield, instead of
+        // doubling repeatedly while inserting them
+        self.reserve(iter.size_hint().0);
+        for (key, value) in iter {
+            let _ = self.insert_pair(key, value);
         }
-        for i in self.block_range(block_index) {
-            if *key == self.main_table[i].0 {
-                let key_value_pair = self.main_table[i].clone();
-                self.main_table[i] = self.main_table[self.block_end(block_index)-1].clone();
-                self.meta_data[block_index].gap += 1;
-                self.no_elements_in_main_table -= 1;
-                remove_value = Some(key_value_pair);
-                break
-            }
-        }
-        remove_value
     }
 }
 
-impl<Key, Value> Named for SlickHash<Key, Value> {
+impl<Key, Value, S> HashTableRemove<Key, Value> for SlickHash<Key, Value, S>
+where
+    Key: Clone + Eq + PartialEq + Hash + Default,
+    Value: Clone + Default,
+    S: BuildHasher + Clone,
+{
+    fn remove_entry(&mut self, key: &Key) -> Option<(Key, Value)> {
+        let key_value_pair = self.take_entry(key)?;
+        self.removals_since_compaction += 1;
+        Some(key_value_pair)
+    }
+}
+
+impl<Key, Value, S> Named for SlickHash<Key, Value, S> {
     fn name() -> String {
         "SlickHash".into()
     }
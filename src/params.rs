@@ -0,0 +1,107 @@
+/// How far a block without space raises its threshold, bumping every entry below it into the
+/// backyard. The new threshold is always at least one above the smallest threshold among the
+/// block's keys and the key to insert, so that at least one of them leaves the block.
+#[derive(Clone, Copy, Debug)]
+pub enum ThresholdPolicy {
+    /// One above the smallest threshold: bumps as few entries as possible
+    Minimal,
+    /// The median threshold: bumps about half of the block at once, so that it doesn't have to
+    /// bump again soon, at the cost of a larger backyard
+    Aggressive,
+    /// Computes the new threshold from the thresholds of the block's keys and the key to
+    /// insert, sorted ascending
+    Custom(fn(&[usize]) -> usize),
+}
+
+/// Hyperparameters of a SlickHash
+#[derive(Clone, Copy, Debug)]
+pub struct SlickParams {
+    /// Nominal number of main table slots per block
+    pub block_size: usize,
+    /// Number of entries at which a block bumps instead of growing further
+    pub max_slick_size: usize,
+    /// Number of slots a block may be shifted to the right of its nominal start
+    pub max_offset: usize,
+    /// Number of distinct key thresholds
+    pub max_threshold: usize,
+    pub threshold_policy: ThresholdPolicy,
+}
+
+// Block size of the default configuration, needed where SlickParams::default can't be called
+pub(crate) const DEFAULT_BLOCK_SIZE: usize = 10;
+
+impl Default for SlickParams {
+    // The configuration proposed by the authors
+    fn default() -> Self {
+        let block_size = DEFAULT_BLOCK_SIZE;
+        SlickParams {
+            block_size,
+            max_slick_size: block_size * 2,
+            max_offset: block_size,
+            max_threshold: block_size,
+            threshold_policy: ThresholdPolicy::Minimal,
+        }
+    }
+}
+
+/// A violated relationship between the hyperparameters of a SlickHash
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParamError {
+    ZeroBlockSize,
+    /// The capacity isn't a whole number of blocks
+    CapacityNotBlockMultiple,
+    /// A block must be allowed to hold at least `block_size` entries
+    MaxSlickSizeBelowBlockSize,
+    /// Blocks must be allowed to shift right, by at most one block
+    MaxOffsetOutOfRange,
+    ZeroMaxThreshold,
+}
+
+impl std::fmt::Display for ParamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            ParamError::ZeroBlockSize => "block_size must be positive",
+            ParamError::CapacityNotBlockMultiple => "capacity must be a multiple of block_size",
+            ParamError::MaxSlickSizeBelowBlockSize => "max_slick_size must be at least block_size",
+            ParamError::MaxOffsetOutOfRange => "max_offset must be positive and at most block_size",
+            ParamError::ZeroMaxThreshold => "max_threshold must be positive",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+impl SlickParams {
+    /// Checks every constraint between the hyperparameters, reporting all violated ones at
+    /// once. The capacity of a table is checked separately when it is created.
+    pub fn validate(&self) -> Result<(), Vec<ParamError>> {
+        let mut violations = Vec::new();
+        if self.block_size == 0 {
+            violations.push(ParamError::ZeroBlockSize);
+        }
+        if self.max_slick_size < self.block_size {
+            violations.push(ParamError::MaxSlickSizeBelowBlockSize);
+        }
+        if self.max_offset == 0 || self.max_offset > self.block_size {
+            violations.push(ParamError::MaxOffsetOutOfRange);
+        }
+        if self.max_threshold == 0 {
+            violations.push(ParamError::ZeroMaxThreshold);
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    // Finds the first violated constraint for a table of the given capacity
+    pub(crate) fn check(&self, capacity: usize) -> Result<(), ParamError> {
+        self.validate().map_err(|violations| violations[0])?;
+        if !capacity.is_multiple_of(self.block_size) {
+            return Err(ParamError::CapacityNotBlockMultiple)
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1362 @@
+use super::*;
+use crate::hash_table::TypeName;
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::hash::{BuildHasherDefault, Hasher};
+
+// Hashes a u64 key to itself, so that a test picks the block of every key
+#[derive(Clone, Copy, Default)]
+struct IdentityHasher(u64);
+
+impl Hasher for IdentityHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 << 8) | byte as u64;
+        }
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.0 = i;
+    }
+}
+
+type BuildIdentity = BuildHasherDefault<IdentityHasher>;
+
+// Deterministic xorshift stream, so that failures replay
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+}
+
+#[cfg(feature = "rand")]
+impl rand::RngCore for Random {
+    fn next_u32(&mut self) -> u32 {
+        self.next() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.next()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next().to_le_bytes()[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+fn random_keys(seed: u64, count: usize) -> Vec<u64> {
+    let mut random = Random(seed);
+    (0..count).map(|_| random.next()).collect()
+}
+
+// The first `count` keys hashing into the block
+fn keys_in_block(table: &SlickHash<u64, u64>, block_index: usize, count: usize) -> Vec<u64> {
+    (0..).filter(|key| table.hash_block_index(key) == block_index).take(count).collect()
+}
+
+#[test]
+fn slide_from_the_last_block_stays_inside_the_table() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(100);
+    let last = table.number_of_blocks - 1;
+    let last_keys = keys_in_block(&table, last, 3);
+    let keys = keys_in_block(&table, last - 1, 17);
+    for &key in &last_keys {
+        let _ = table.try_insert((key, key));
+    }
+    // The block before the last one takes slot after slot from the last block, the final one
+    // being the last block's only remaining gap slot at the very end of the table
+    for (i, &key) in keys.iter().enumerate() {
+        let _ = table.try_insert((key, key));
+        assert_eq!(table.meta_data[last].offset, i.saturating_sub(9));
+    }
+    let last_block = &table.meta_data[last];
+    assert_eq!((last_block.offset, last_block.gap, table.block_range(last).len()), (7, 0, 3));
+    assert_eq!(table.block_end(last), table.main_table_size);
+    for key in last_keys.iter().chain(&keys) {
+        assert_eq!(table.get(key), Some(key));
+    }
+}
+
+// Block 3 overflows into block 4 and frees a slot again, which block 4 later takes back in a
+// slide from the left, leaving both blocks without gap. Returns the table and the keys of block 4.
+fn table_with_gapless_lender(refill: bool) -> (SlickHash<u64, u64>, Vec<u64>) {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(100);
+    table.set_refill_gapless_left_on_remove(refill);
+    let block_3_keys = keys_in_block(&table, 3, 11);
+    for &key in &block_3_keys {
+        let _ = table.try_insert((key, key));
+    }
+    assert_eq!(table.remove(&block_3_keys[0]), Some(block_3_keys[0]));
+    let block_4_keys = keys_in_block(&table, 4, 10);
+    for &key in &block_4_keys {
+        let _ = table.try_insert((key, key));
+    }
+    assert_eq!((table.meta_data[3].gap, table.meta_data[4].offset, table.meta_data[4].gap), (0, 0, 0));
+    (table, block_4_keys)
+}
+
+#[test]
+fn removal_refills_a_gapless_left_neighbour_if_enabled() {
+    // The second removal leaves block 4 a free slot to spare
+    let (mut table, keys) = table_with_gapless_lender(true);
+    assert_eq!(table.remove(&keys[0]), Some(keys[0]));
+    assert_eq!((table.meta_data[3].gap, table.meta_data[4].offset, table.meta_data[4].gap), (0, 0, 1));
+    assert_eq!(table.remove(&keys[1]), Some(keys[1]));
+    assert_eq!((table.meta_data[3].gap, table.meta_data[4].offset, table.meta_data[4].gap), (1, 1, 1));
+    for key in keys_in_block(&table, 3, 11).into_iter().skip(1).chain(keys.into_iter().skip(2)) {
+        assert_eq!(table.get(&key), Some(&key));
+    }
+
+    let (mut table, keys) = table_with_gapless_lender(false);
+    for key in &keys[..2] {
+        assert_eq!(table.remove(key), Some(*key));
+    }
+    assert_eq!((table.meta_data[3].gap, table.meta_data[4].offset, table.meta_data[4].gap), (0, 0, 2));
+}
+
+#[test]
+fn type_name_labels_tables_behind_trait_objects() {
+    let tables: Vec<Box<dyn TypeName>> = vec![
+        Box::new(SlickHash::<u64, u64>::with_capacity(10)),
+        Box::new(HashMap::<u64, u64>::new()),
+    ];
+    let names: Vec<String> = tables.iter().map(|table| table.type_name()).collect();
+    assert_eq!(names, ["SlickHash", "std::collection::HashMap"]);
+}
+
+#[test]
+fn removing_keys_below_the_threshold_leaves_the_block_alone() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(100);
+    let keys = keys_in_block(&table, 0, 1000);
+    for &key in &keys[..45] {
+        let _ = table.try_insert((key, key));
+    }
+    let threshold = table.meta_data[0].threshold;
+    assert!(threshold > 0);
+    let below_threshold = |key: &&u64| table.hash_threshold(key) < threshold;
+    let bumped: Vec<u64> = keys[..45].iter().filter(below_threshold).copied().collect();
+    let absent: Vec<u64> = keys[45..].iter().filter(below_threshold).take(5).copied().collect();
+    assert!(!bumped.is_empty() && absent.len() == 5);
+    let layout = |table: &SlickHash<u64, u64>| -> Vec<(usize, usize, usize)> {
+        table.meta_data.iter().map(|meta_data| (meta_data.offset, meta_data.gap, meta_data.threshold)).collect()
+    };
+    let blocks = layout(&table);
+    for key in &absent {
+        assert_eq!(table.remove_entry(key), None);
+    }
+    for key in &bumped {
+        assert_eq!(table.remove_entry(key), Some((*key, *key)));
+    }
+    assert_eq!(table.len(), 45 - bumped.len());
+    assert_eq!(layout(&table), blocks);
+}
+
+#[test]
+fn failed_insert_all_or_nothing_leaves_the_table_unchanged() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(100);
+    // Crowding block 0 raises its threshold, so that some of its keys can't be placed strictly
+    let crowded = keys_in_block(&table, 0, 1000);
+    for &key in &crowded[..45] {
+        let _ = table.try_insert((key, key));
+    }
+    let threshold = table.meta_data[0].threshold;
+    let rejected = *crowded[45..].iter().find(|key| table.hash_threshold(key) < threshold).unwrap();
+    let pairs = [
+        (keys_in_block(&table, 5, 1)[0], 1),
+        (keys_in_block(&table, 6, 1)[0], 2),
+        (rejected, 3),
+        (keys_in_block(&table, 7, 1)[0], 4),
+    ];
+    let contents = |table: &SlickHash<u64, u64>| {
+        let layout: Vec<(usize, usize, usize)> = table.meta_data.iter()
+            .map(|meta_data| (meta_data.offset, meta_data.gap, meta_data.threshold))
+            .collect();
+        let blocks: Vec<Vec<(u64, u64)>> = (0..table.number_of_blocks)
+            .map(|block_index| table.main_table[table.block_range(block_index)].to_vec())
+            .collect();
+        let mut backyard: Vec<(u64, u64)> = table.backyard.iter().map(|(&key, &value)| (key, value)).collect();
+        backyard.sort_unstable();
+        (layout, blocks, backyard)
+    };
+    let before = contents(&table);
+    assert_eq!(table.insert_all_or_nothing(&pairs), Err(2));
+    assert_eq!(contents(&table), before);
+    assert_eq!((table.insertions_since_compaction(), table.removals_since_compaction()), (45, 0));
+
+    assert_eq!(table.insert_all_or_nothing(&[pairs[0], pairs[1], pairs[3]]), Ok(()));
+    assert_eq!((table.insertions_since_compaction(), table.removals_since_compaction()), (48, 0));
+    for (key, value) in [pairs[0], pairs[1], pairs[3]] {
+        assert_eq!(table.get(&key), Some(&value));
+    }
+}
+
+// A table implementing only the required methods of HashTableBase
+struct VecTable(Vec<(u64, u64)>);
+
+impl HashTableBase<u64, u64> for VecTable {
+    fn with_capacity(capacity: impl Capacity) -> Self {
+        VecTable(Vec::with_capacity(capacity.capacity()))
+    }
+
+    fn try_insert(&mut self, (key, value): (u64, u64)) -> Insertion<'_, u64> {
+        match self.0.iter().position(|&(present_key, _)| present_key == key) {
+            Some(index) => Insertion::Occupied(&mut self.0[index].1),
+            None => {
+                self.0.push((key, value));
+                Insertion::Inserted(&mut self.0.last_mut().unwrap().1)
+            }
+        }
+    }
+
+    fn get(&self, key: &u64) -> Option<&u64> {
+        self.0.iter().find(|(present_key, _)| present_key == key).map(|(_, value)| value)
+    }
+}
+
+// Inserts through the trait, reading the previous key in between
+fn insert_owned_while_reading<T: HashTableBase<u64, u64>>(mut table: T) {
+    for key in 0..100 {
+        assert_eq!(table.insert_owned(key, key), None);
+        if key > 0 {
+            assert_eq!(table.get(&(key - 1)), Some(&(key - 1)));
+        }
+    }
+    for key in 0..100 {
+        assert_eq!(table.insert_owned(key, key + 1), Some(key));
+        assert_eq!(table.get(&key), Some(&(key + 1)));
+    }
+}
+
+#[test]
+fn insert_owned_returns_the_previous_value() {
+    insert_owned_while_reading(SlickHash::<u64, u64>::with_capacity(200));
+    insert_owned_while_reading(HashMap::new());
+    insert_owned_while_reading(BTreeMap::new());
+    insert_owned_while_reading(VecTable::with_capacity(0));
+}
+
+#[test]
+fn u32_keys_round_trip_in_half_the_slot_memory() {
+    let keys: Vec<u32> = random_keys(407, 900).into_iter().map(|key| key as u32).collect();
+    let mut table: SlickHash32<u32> = SlickHash::with_capacity(1000);
+    let mut wide: SlickHash<u64, u32> = SlickHash::with_capacity(1000);
+    for &key in &keys {
+        assert!(table.try_insert((key, key)).is_inserted());
+        let _ = wide.try_insert((key as u64, key));
+    }
+    for &key in &keys[..450] {
+        assert_eq!(table.remove_entry(&key), Some((key, key)));
+        let _ = wide.remove_entry(&(key as u64));
+    }
+    for &key in &keys[..450] {
+        assert_eq!(table.get(&key), None);
+    }
+    for &key in &keys[450..] {
+        assert_eq!(table.get(&key), Some(&key));
+    }
+    assert_eq!(table.len(), wide.len());
+    // The main table holds the same number of slots, each of them half as wide
+    assert_eq!(table.main_table.len(), wide.main_table.len());
+    assert_eq!(2 * size_of_val(table.main_table.as_slice()), size_of_val(wide.main_table.as_slice()));
+}
+
+fn is_empty_until_inserted<T: HashTableBase<u64, u64>>(mut table: T) {
+    assert!(table.is_empty());
+    assert_eq!(table.len(), 0);
+    let _ = table.try_insert((1, 1));
+    assert!(!table.is_empty());
+    assert_eq!(table.len(), 1);
+}
+
+#[test]
+fn len_and_is_empty_through_the_trait() {
+    is_empty_until_inserted(SlickHash::<u64, u64>::with_capacity(10));
+    is_empty_until_inserted(HashMap::new());
+    is_empty_until_inserted(BTreeMap::new());
+}
+
+#[test]
+fn direct_spills_grow_the_table_like_inserts() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(10);
+    for key in 0..100 {
+        let _ = table.insert_into_backyard_pub(key, key);
+        assert!(table.len() <= table.capacity());
+    }
+    for key in 0..100 {
+        assert_eq!(table.get(&key), Some(&key));
+    }
+}
+
+#[test]
+fn direct_spill_removed_through_threshold_routing_is_forgotten() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(1000);
+    let mut keys = keys_in_block(&table, 3, 60);
+    keys.sort_by_key(|key| table.hash_threshold(key));
+    let spilled = keys[0];
+    let _ = table.insert_into_backyard_pub(spilled, 0);
+    // Crowding the block raises its threshold above the spilled key's
+    for &key in &keys[1..] {
+        let _ = table.try_insert((key, key));
+    }
+    assert!(table.hash_threshold(&spilled) < table.meta_data[3].threshold);
+    assert_eq!(table.remove_entry(&spilled), Some((spilled, 0)));
+    assert!(table.direct_spills.is_empty());
+}
+
+#[test]
+fn rebalancing_after_retain_leaves_direct_spills_in_the_backyard() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(100);
+    for key in 0..50 {
+        let _ = table.try_insert((key, key));
+    }
+    for key in 100..110 {
+        let _ = table.insert_into_backyard_pub(key, key);
+    }
+    // Empties every block, so that all of them are rebalanced
+    table.retain(|&key, _| key >= 100);
+    for key in 100..110 {
+        assert!(table.backyard.contains_key(&key));
+        assert!(table.direct_spills.contains(&key));
+        assert_eq!(table.get(&key), Some(&key));
+    }
+    assert_eq!(table.len(), 10);
+}
+
+#[test]
+fn with_capacity_pow2_selects_blocks_by_the_top_bits() {
+    let table: SlickHash<u64, u64> = SlickHash::with_capacity_pow2(1000);
+    // 100 blocks are requested, 128 is the nearest power of two
+    assert_eq!(table.number_of_blocks(), 128);
+    assert!(table.uses_masked_hashing());
+    for hash in random_keys(418, 1000).into_iter().chain([0, u64::MAX]) {
+        assert_eq!(table.block_for_hash(hash), (hash >> 57) as usize);
+    }
+    let mut block_used = vec![false; table.number_of_blocks()];
+    for key in 0..10_000 {
+        block_used[table.hash_block_index(&key)] = true;
+    }
+    assert!(block_used.iter().all(|&used| used));
+}
+
+fn increment_through_the_trait<T: HashTableBase<u64, u64>>(mut table: T) {
+    let _ = table.try_insert((7, 1));
+    *table.get_mut(&7).unwrap() += 1;
+    assert_eq!(table.get(&7), Some(&2));
+    assert_eq!(table.get_mut(&8), None);
+}
+
+#[test]
+fn get_mut_through_the_trait() {
+    increment_through_the_trait(SlickHash::<u64, u64>::with_capacity(10));
+    increment_through_the_trait(HashMap::new());
+    increment_through_the_trait(BTreeMap::new());
+}
+
+#[test]
+fn fast_mix_spreads_keys_evenly_over_the_blocks() {
+    let table: SlickHash<u64, u64> = SlickHash::with_capacity(1000);
+    // Sequential keys and keys differing only in their high bits
+    for keys in [(0..100_000).collect::<Vec<u64>>(), (0..100_000).map(|i| i << 40).collect()] {
+        let mut block_loads = vec![0usize; table.number_of_blocks];
+        for key in &keys {
+            block_loads[table.hash_block_index(key)] += 1;
+        }
+        // 1000 keys are expected per block, with a standard deviation of about 32
+        assert!(block_loads.iter().all(|&load| (850..1150).contains(&load)), "{block_loads:?}");
+    }
+}
+
+#[test]
+fn inserting_a_present_key_reports_its_own_entry() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(100);
+    // Keys of a block that doesn't start at the beginning of the table
+    let keys = keys_in_block(&table, 5, 3);
+    for &key in &keys {
+        let _ = table.try_insert((key, key));
+    }
+    for &key in &keys {
+        let mut insertion = table.try_insert((key, 0));
+        assert!(!insertion.is_inserted());
+        assert_eq!(*insertion.as_mut(), key);
+    }
+    assert_eq!(table.len(), 3);
+}
+
+#[test]
+fn insertion_tells_inserted_and_occupied_apart() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(10);
+    let inserted = table.try_insert((1, 10));
+    assert!(inserted.is_inserted() && !inserted.is_occupied());
+    *inserted.into_mut() += 1;
+    let occupied = table.try_insert((1, 0));
+    assert!(occupied.is_occupied() && !occupied.is_inserted());
+    assert_eq!(*occupied.into_mut(), 11);
+}
+
+thread_local! {
+    static HASHES: Cell<usize> = const { Cell::new(0) };
+}
+
+// A key counting how often it is hashed on the current thread
+#[derive(Clone, Default, PartialEq, Eq)]
+struct CountedKey(u64);
+
+impl Hash for CountedKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        HASHES.set(HASHES.get() + 1);
+        self.0.hash(state);
+    }
+}
+
+fn hashes_during(operation: impl FnOnce()) -> usize {
+    let before = HASHES.get();
+    operation();
+    HASHES.get() - before
+}
+
+#[test]
+fn inserts_and_lookups_hash_the_key_once() {
+    // Sparse enough that no insertion bumps or grows, which hash other keys
+    let mut table: SlickHash<CountedKey, u64> = SlickHash::with_capacity(1000);
+    for key in 0..200 {
+        assert_eq!(hashes_during(|| assert!(table.try_insert((CountedKey(key), key)).is_inserted())), 1);
+    }
+    assert_eq!(table.entry_count_by_location(), (200, 0));
+    for key in 0..200 {
+        assert_eq!(hashes_during(|| assert_eq!(table.get(&CountedKey(key)), Some(&key))), 1);
+        assert_eq!(hashes_during(|| assert!(table.try_insert((CountedKey(key), 0)).is_occupied())), 1);
+    }
+}
+
+#[test]
+fn direct_spills_are_forgotten_by_bulk_removals() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(100);
+    for key in 0..20 {
+        let _ = table.insert_into_backyard_pub(key, key);
+    }
+    table.retain(|&key, _| key >= 10);
+    assert_eq!(table.direct_spills.len(), 10);
+    assert_eq!(table.bulk_remove_sorted(&(10..15).collect::<Vec<u64>>()), 5);
+    assert_eq!(table.direct_spills.len(), 5);
+    let _ = table.pop();
+    assert_eq!(table.direct_spills.len(), 4);
+    let remaining: Vec<u64> = table.backyard.keys().copied().collect();
+    for key in remaining {
+        assert_eq!(table.remove_entry(&key), Some((key, key)));
+    }
+    assert!(table.direct_spills.is_empty());
+}
+
+#[test]
+fn drain_backyard_into_main_never_resizes() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(100);
+    let keys = random_keys(458, 100);
+    for &key in &keys {
+        let _ = table.try_insert((key, key));
+    }
+    assert_eq!(table.len(), table.capacity());
+    // A full backyard would make any spilling insertion grow the table
+    table.set_backyard_soft_limit(Some(0));
+    let still_spilled = table.drain_backyard_into_main();
+    assert_eq!(table.capacity(), 100);
+    assert_eq!(still_spilled, table.entry_count_by_location().1);
+    for key in &keys {
+        assert_eq!(table.get(key), Some(key));
+    }
+}
+
+// Applies random operations of every kind to a small, crowded table, checking after each
+// one that lookups find every entry and that the table agrees with a HashMap
+#[test]
+fn random_operations_keep_routing_consistent() {
+    for seed in 1..=20 {
+        let mut random = Random(seed * 0x9E37_79B9);
+        let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(20);
+        table.set_refill_gapless_left_on_remove(seed % 2 == 0);
+        let mut reference: HashMap<u64, u64> = HashMap::new();
+        for step in 0..2000 {
+            let key = random.below(400);
+            let value = random.next();
+            match random.below(100) {
+                0..=34 => {
+                    let _ = table.try_insert((key, value));
+                    reference.entry(key).or_insert(value);
+                }
+                35..=44 => assert_eq!(table.insert_owned(key, value), reference.insert(key, value)),
+                45..=69 => assert_eq!(table.remove_entry(&key), reference.remove_entry(&key)),
+                70..=74 => {
+                    let _ = table.insert_into_backyard_pub(key, value);
+                    reference.entry(key).or_insert(value);
+                }
+                75..=79 => {
+                    *table.get_mut_or_spill(key, value) += 1;
+                    *reference.entry(key).or_insert(value) += 1;
+                }
+                80..=84 => {
+                    *table.entry(key).or_insert(value) += 1;
+                    *reference.entry(key).or_insert(value) += 1;
+                }
+                85..=86 => {
+                    let keys: Vec<u64> = (0..8).map(|_| random.below(400)).collect();
+                    let removed = keys.iter().filter(|&key| reference.remove(key).is_some()).count();
+                    let mut unique = keys.clone();
+                    unique.sort_unstable();
+                    unique.dedup();
+                    assert_eq!(table.bulk_remove_sorted(&unique), removed);
+                }
+                87 => {
+                    let modulus = random.below(7) + 2;
+                    table.retain(|key, _| key % modulus != 0);
+                    reference.retain(|key, _| key % modulus != 0);
+                }
+                88 => table.rehash_in_place(),
+                89 => {
+                    let _ = table.drain_backyard_into_main();
+                }
+                90 => table.reserve(random.below(50) as usize),
+                91 => table.shrink_to_fit(),
+                #[cfg(feature = "rand")]
+                93 => {
+                    if let Some((key, value)) = table.remove_random(&mut random) {
+                        assert_eq!(reference.remove(&key), Some(value));
+                    }
+                }
+                94 => {
+                    if let Some((key, value)) = table.pop() {
+                        assert_eq!(reference.remove(&key), Some(value));
+                    }
+                }
+                _ => assert_eq!(table.get(&key), reference.get(&key)),
+            }
+            table.assert_routing_consistency();
+            assert_eq!(table.len(), reference.len(), "seed {seed}, step {step}");
+            assert!(table.len() <= table.capacity(), "seed {seed}, step {step}");
+        }
+        for (key, value) in &reference {
+            assert_eq!(table.get(key), Some(value));
+        }
+    }
+}
+
+#[test]
+fn growing_places_like_inserting_fresh_in_canonical_order() {
+    let keys = random_keys(437, 900);
+    let mut grown: SlickHash<u64, u64> = SlickHash::with_capacity(1000);
+    for &key in &keys {
+        let _ = grown.try_insert((key, key));
+    }
+    grown.reserve(grown.capacity());
+    let mut fresh: SlickHash<u64, u64> = SlickHash::with_capacity(grown.capacity());
+    let mut sorted_keys = keys.clone();
+    sorted_keys.sort_unstable_by_key(|key| {
+        let hashed = fresh.hash_key(key);
+        (hashed.block_index, Reverse(hashed.threshold), hashed.hash)
+    });
+    for &key in &sorted_keys {
+        let _ = fresh.try_insert((key, key));
+    }
+    let layout = |table: &SlickHash<u64, u64>| -> Vec<(usize, usize, usize, Vec<u64>)> {
+        (0..table.number_of_blocks)
+            .map(|block_index| {
+                let meta_data = &table.meta_data[block_index];
+                let keys = table.main_table[table.block_range(block_index)].iter().map(|&(key, _)| key).collect();
+                (meta_data.offset, meta_data.gap, meta_data.threshold, keys)
+            })
+            .collect()
+    };
+    assert_eq!(layout(&grown), layout(&fresh));
+    assert_eq!(grown.entry_count_by_location(), fresh.entry_count_by_location());
+    for key in &keys {
+        assert_eq!(grown.get(key), Some(key));
+    }
+}
+
+thread_local! {
+    static KEY_CLONES: Cell<usize> = const { Cell::new(0) };
+}
+
+// A key counting how often it is cloned on the current thread
+#[derive(Default, PartialEq, Eq, Hash)]
+struct CloneCountedKey(String);
+
+impl Clone for CloneCountedKey {
+    fn clone(&self) -> Self {
+        KEY_CLONES.set(KEY_CLONES.get() + 1);
+        CloneCountedKey(self.0.clone())
+    }
+}
+
+#[test]
+fn update_value_does_not_clone_the_key() {
+    let mut table: SlickHash<CloneCountedKey, u64> = SlickHash::with_capacity(100);
+    for i in 0..50 {
+        let _ = table.try_insert((CloneCountedKey(format!("key {i}")), i));
+    }
+    let key = CloneCountedKey("key 7".into());
+    let absent = CloneCountedKey("absent".into());
+    let clones = KEY_CLONES.get();
+    assert!(table.update_value(&key, 70));
+    assert!(!table.update_value(&absent, 0));
+    assert_eq!(KEY_CLONES.get(), clones);
+    assert_eq!(table.get(&key), Some(&70));
+    assert_eq!(table.get(&absent), None);
+}
+
+#[test]
+fn rehash_in_place_keeps_every_entry() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(10);
+    for key in 0..10 {
+        let _ = table.try_insert((key, key));
+    }
+    for key in 100..120 {
+        let _ = table.insert_into_backyard_pub(key, key);
+    }
+    table.rehash_in_place();
+    assert_eq!(table.len(), 30);
+    assert!(table.capacity() >= table.len());
+    for key in (0..10).chain(100..120) {
+        assert_eq!(table.get(&key), Some(&key));
+    }
+    table.assert_routing_consistency();
+}
+
+#[test]
+fn insert_into_table_without_blocks() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(0usize);
+    assert_eq!(table.get(&1), None);
+    assert_eq!(table.remove_entry(&1), None);
+    assert!(table.try_insert((1, 2)).is_inserted());
+    assert_eq!(table.get(&1), Some(&2));
+
+    // No blocks but a spilled key, the state the backyard-only paths of get and remove handle
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(0usize);
+    let _ = table.insert_into_backyard(1, 1);
+    table.direct_spills.insert(1);
+    assert!(table.try_insert((1, 2)).is_occupied());
+    assert!(table.try_insert((2, 2)).is_inserted());
+    assert_eq!(table.get(&1), Some(&1));
+    assert_eq!(table.get(&2), Some(&2));
+    assert_eq!(table.len(), 2);
+    assert_eq!(table.remove_entry(&1), Some((1, 1)));
+    assert!(table.direct_spills.is_empty());
+}
+
+// The keys 0..800 with themselves as values, in ascending block order for 1000 slots
+fn block_sorted_pairs() -> Vec<(u64, u64)> {
+    let probe: SlickHash<u64, u64> = SlickHash::with_capacity(1000);
+    let mut pairs: Vec<(u64, u64)> = (0..800).map(|key| (key, key)).collect();
+    pairs.sort_by_key(|(key, _)| probe.hash_key(key).block_index);
+    pairs
+}
+
+#[test]
+fn from_block_sorted_builds_from_block_ordered_pairs() {
+    let pairs = block_sorted_pairs();
+    let table: SlickHash<u64, u64> = SlickHash::from_block_sorted(1000, &pairs);
+    assert_eq!(table.len(), pairs.len());
+    for (key, value) in &pairs {
+        assert_eq!(table.get(key), Some(value));
+    }
+    table.assert_routing_consistency();
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "pairs are not sorted by block")]
+fn from_block_sorted_rejects_pairs_out_of_block_order() {
+    let mut pairs = block_sorted_pairs();
+    pairs.reverse();
+    let _: SlickHash<u64, u64> = SlickHash::from_block_sorted(1000, &pairs);
+}
+
+fn params_with(change: impl FnOnce(&mut SlickParams)) -> SlickParams {
+    let mut params = SlickParams::default();
+    change(&mut params);
+    params
+}
+
+fn param_error(capacity: usize, params: SlickParams) -> Option<ParamError> {
+    SlickHash::<u64, u64>::try_with_params(capacity, params).err()
+}
+
+#[test]
+fn try_with_params_rejects_a_zero_block_size() {
+    assert_eq!(param_error(100, params_with(|params| params.block_size = 0)), Some(ParamError::ZeroBlockSize));
+}
+
+#[test]
+fn try_with_params_rejects_a_capacity_of_partial_blocks() {
+    assert_eq!(param_error(105, SlickParams::default()), Some(ParamError::CapacityNotBlockMultiple));
+}
+
+#[test]
+fn try_with_params_rejects_a_max_slick_size_below_the_block_size() {
+    let params = params_with(|params| params.max_slick_size = params.block_size - 1);
+    assert_eq!(param_error(100, params), Some(ParamError::MaxSlickSizeBelowBlockSize));
+}
+
+#[test]
+fn try_with_params_rejects_a_max_offset_out_of_range() {
+    let params = params_with(|params| params.max_offset = 0);
+    assert_eq!(param_error(100, params), Some(ParamError::MaxOffsetOutOfRange));
+    let params = params_with(|params| params.max_offset = params.block_size + 1);
+    assert_eq!(param_error(100, params), Some(ParamError::MaxOffsetOutOfRange));
+}
+
+#[test]
+fn try_with_params_rejects_a_zero_max_threshold() {
+    assert_eq!(param_error(100, params_with(|params| params.max_threshold = 0)), Some(ParamError::ZeroMaxThreshold));
+}
+
+#[test]
+fn try_with_params_accepts_valid_parameters() {
+    let table = SlickHash::<u64, u64>::try_with_params(100, SlickParams::default()).unwrap_or_else(|error| panic!("{error}"));
+    assert_eq!(table.capacity(), 100);
+}
+
+#[test]
+fn draining_the_backyard_keeps_the_capacity() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(1000);
+    for key in 0..300 {
+        let _ = table.insert_into_backyard_pub(key, key);
+    }
+    for key in 300..1000 {
+        let _ = table.try_insert((key, key));
+    }
+    let capacity = table.capacity();
+    // A full backyard would make any spilling insertion grow the table
+    table.set_backyard_soft_limit(Some(0));
+    let spilled = table.drain_backyard_into_main();
+    assert_eq!(table.capacity(), capacity);
+    assert_eq!(spilled, table.backyard.len());
+    assert!(spilled < 300);
+    assert_eq!(table.len(), 1000);
+    for key in 0..1000 {
+        assert_eq!(table.get(&key), Some(&key));
+    }
+    table.assert_routing_consistency();
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Insert(u64, u64),
+    InsertOwned(u64, u64),
+    Remove(u64),
+    Get(u64),
+    SpillDirectly(u64, u64),
+    RehashInPlace,
+    DrainBackyard,
+}
+
+// Tables the sequences run on have this many blocks under the identity hasher
+const SEQUENCE_BLOCKS: u64 = 10;
+
+// The i-th key of the block, in a table of SEQUENCE_BLOCKS blocks under the identity hasher
+fn key_in_block(block_index: u64, i: u64) -> u64 {
+    block_index * (u64::MAX / SEQUENCE_BLOCKS + 1) + i
+}
+
+// Operation sequences known to stress the slide, bump and remove edges of the layout
+fn tricky_sequences() -> Vec<Vec<Op>> {
+    let last = SEQUENCE_BLOCKS - 1;
+    let mut sequences = Vec::new();
+
+    // Fills one block far beyond max_slick_size, bumping again and again, then empties it
+    let fill_one_block: Vec<Op> = (0..45).map(|i| Op::Insert(key_in_block(0, i), i)).collect();
+    let empty_one_block = (0..45).map(|i| Op::Remove(key_in_block(0, i)));
+    sequences.push(fill_one_block.iter().copied().chain(empty_one_block).collect());
+
+    // The same at the end of the table, where the last block can only take gaps from the left
+    sequences.push((0..45).map(|i| Op::Insert(key_in_block(last, i), i))
+        .chain((0..45).step_by(2).map(|i| Op::Remove(key_in_block(last, i))))
+        .chain((0..45).map(|i| Op::Get(key_in_block(last, i))))
+        .collect());
+
+    // Alternates inserts and removals on both sides of a block boundary
+    let mut boundary = Vec::new();
+    for i in 0..30 {
+        boundary.push(Op::Insert(key_in_block(4, 100 + i), i));
+        boundary.push(Op::Insert(key_in_block(5, i), i));
+        if i % 3 == 2 {
+            boundary.push(Op::Remove(key_in_block(4, 100 + i - 1)));
+            boundary.push(Op::Remove(key_in_block(5, i - 2)));
+        }
+    }
+    sequences.push(boundary);
+
+    // Overfills neighbouring blocks one after the other, pushing offsets to max_offset
+    sequences.push((0..6).flat_map(|block_index| (0..19).map(move |i| Op::Insert(key_in_block(block_index, i), i))).collect());
+
+    // Keys of a block with a raised threshold that were never inserted, removed and looked up
+    sequences.push(fill_one_block.iter().copied()
+        .chain((1000..1010).flat_map(|i| [Op::Remove(key_in_block(0, i)), Op::Get(key_in_block(0, i))]))
+        .collect());
+
+    // Direct spills mixed with regular inserts, removals and rebuilds of the same block
+    let mut spills = Vec::new();
+    for i in 0..25 {
+        spills.push(Op::SpillDirectly(key_in_block(2, i), i));
+        spills.push(Op::Insert(key_in_block(2, 50 + i), i));
+        spills.push(Op::InsertOwned(key_in_block(2, i), i + 1));
+        if i % 5 == 4 {
+            spills.push(Op::Remove(key_in_block(2, i - 2)));
+            spills.push(Op::RehashInPlace);
+        }
+    }
+    spills.push(Op::DrainBackyard);
+    sequences.push(spills);
+
+    // Fills the whole table until it grows, then removes everything again
+    let keys: Vec<u64> = (0..SEQUENCE_BLOCKS).flat_map(|block_index| (0..12).map(move |i| key_in_block(block_index, i))).collect();
+    sequences.push(keys.iter().map(|&key| Op::Insert(key, key)).chain(keys.iter().map(|&key| Op::Remove(key))).collect());
+
+    sequences
+}
+
+#[test]
+fn tricky_sequences_agree_with_hashmap() {
+    for (sequence_index, sequence) in tricky_sequences().into_iter().enumerate() {
+        let mut table: SlickHash<u64, u64, BuildIdentity> =
+            SlickHash::with_capacity_and_hasher(SEQUENCE_BLOCKS as usize * SlickParams::default().block_size, BuildIdentity::default());
+        for block_index in 0..SEQUENCE_BLOCKS {
+            assert_eq!(table.hash_key(&key_in_block(block_index, 0)).block_index, block_index as usize);
+        }
+        let mut reference: HashMap<u64, u64> = HashMap::new();
+        for (step, op) in sequence.into_iter().enumerate() {
+            match op {
+                Op::Insert(key, value) => {
+                    let _ = table.try_insert((key, value));
+                    reference.entry(key).or_insert(value);
+                }
+                Op::InsertOwned(key, value) => assert_eq!(table.insert_owned(key, value), reference.insert(key, value)),
+                Op::Remove(key) => assert_eq!(table.remove(&key), reference.remove(&key)),
+                Op::Get(key) => assert_eq!(table.get(&key), reference.get(&key)),
+                Op::SpillDirectly(key, value) => {
+                    let _ = table.insert_into_backyard_pub(key, value);
+                    reference.entry(key).or_insert(value);
+                }
+                Op::RehashInPlace => table.rehash_in_place(),
+                Op::DrainBackyard => {
+                    let _ = table.drain_backyard_into_main();
+                }
+            }
+            table.assert_routing_consistency();
+            assert_eq!(table.len(), reference.len(), "sequence {sequence_index}, step {step}: {op:?}");
+            for block_index in 0..table.number_of_blocks {
+                table.debug_assert_live_len(block_index);
+            }
+        }
+        for (key, value) in &reference {
+            assert_eq!(table.get(key), Some(value), "sequence {sequence_index}");
+        }
+    }
+}
+
+#[test]
+fn removing_an_absent_key_below_the_threshold_changes_nothing() {
+    let mut table: SlickHash<u64, u64, BuildIdentity> =
+        SlickHash::with_capacity_and_hasher(SEQUENCE_BLOCKS as usize * SlickParams::default().block_size, BuildIdentity::default());
+    for i in 0..45 {
+        let _ = table.try_insert((key_in_block(0, i), i));
+    }
+    let threshold = table.meta_data[0].threshold;
+    assert!(threshold > 0);
+    let absent: Vec<u64> = (1000..)
+        .map(|i| key_in_block(0, i))
+        .filter(|key| table.hash_key(key).threshold < threshold)
+        .take(5)
+        .collect();
+    let len = table.len();
+    let blocks: Vec<BlockSummary> = table.iter_blocks().collect();
+    let removals = table.removals_since_compaction();
+    for key in &absent {
+        assert_eq!(table.remove(key), None);
+    }
+    assert_eq!(table.len(), len);
+    assert!(table.iter_blocks().eq(blocks));
+    assert_eq!(table.removals_since_compaction(), removals);
+}
+
+// The part of HashMap's read interface a caller might be written against, for u64 entries
+trait ReadOnlyMap {
+    fn get(&self, key: &u64) -> Option<&u64>;
+    fn contains_key(&self, key: &u64) -> bool;
+    fn len(&self) -> usize;
+    fn values(&self) -> impl Iterator<Item = &u64>;
+}
+
+impl ReadOnlyMap for HashMap<u64, u64> {
+    fn get(&self, key: &u64) -> Option<&u64> {
+        HashMap::get(self, key)
+    }
+    fn contains_key(&self, key: &u64) -> bool {
+        HashMap::contains_key(self, key)
+    }
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+    fn values(&self) -> impl Iterator<Item = &u64> {
+        HashMap::values(self)
+    }
+}
+
+impl ReadOnlyMap for SlickHashView<'_, u64, u64> {
+    fn get(&self, key: &u64) -> Option<&u64> {
+        SlickHashView::get(self, key)
+    }
+    fn contains_key(&self, key: &u64) -> bool {
+        SlickHashView::contains_key(self, key)
+    }
+    fn len(&self) -> usize {
+        SlickHashView::len(self)
+    }
+    fn values(&self) -> impl Iterator<Item = &u64> {
+        SlickHashView::values(self)
+    }
+}
+
+fn summarize(map: &impl ReadOnlyMap) -> (usize, u64, Option<u64>, bool) {
+    (map.len(), map.values().sum(), map.get(&7).copied(), map.contains_key(&1000))
+}
+
+#[test]
+fn view_reads_like_a_hashmap() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(100);
+    let mut reference: HashMap<u64, u64> = HashMap::new();
+    for key in 0..150 {
+        let _ = table.try_insert((key, key * 3));
+        reference.insert(key, key * 3);
+    }
+    let _ = table.insert_into_backyard_pub(1000, 1);
+    reference.insert(1000, 1);
+    assert_eq!(summarize(&table.view()), summarize(&reference));
+}
+
+#[test]
+fn interleaved_inserts_and_removes_keep_block_lengths() {
+    // Inserts and removes crowding the last two blocks, so that the last block's end, which
+    // is computed differently from the others, moves back and forth with its gap
+    let last = SEQUENCE_BLOCKS - 1;
+    let mut table: SlickHash<u64, u64, BuildIdentity> =
+        SlickHash::with_capacity_and_hasher(SEQUENCE_BLOCKS as usize * SlickParams::default().block_size, BuildIdentity::default());
+    let mut reference: HashMap<u64, u64> = HashMap::new();
+    let mut random = Random(467);
+    for _ in 0..3000 {
+        let key = key_in_block(last - random.below(2), random.below(30));
+        if random.below(3) == 0 {
+            assert_eq!(table.remove(&key), reference.remove(&key));
+        } else {
+            let _ = table.try_insert((key, key));
+            reference.entry(key).or_insert(key);
+        }
+        for block_index in 0..table.number_of_blocks {
+            table.debug_assert_live_len(block_index);
+            let slots = &table.main_table[table.block_range(block_index)];
+            assert!(slots.iter().all(|(key, _)| table.hash_key(key).block_index == block_index && reference.contains_key(key)));
+        }
+        assert_eq!(table.len(), reference.len());
+    }
+}
+
+#[test]
+fn try_reserve_reports_impossible_growth() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(100);
+    for key in 0..150 {
+        let _ = table.try_insert((key, key));
+    }
+    let capacity = table.capacity();
+    assert!(table.try_reserve(usize::MAX).is_err());
+    assert!(table.try_reserve(usize::MAX / 2).is_err());
+    assert_eq!(table.capacity(), capacity);
+    assert_eq!(table.len(), 150);
+    assert!(table.try_insert((1000, 1000)).is_inserted());
+    for key in (0..150).chain([1000]) {
+        assert_eq!(table.get(&key), Some(&key));
+    }
+    assert!(table.try_reserve(1000).is_ok());
+    assert!(table.capacity() >= 1151);
+}
+
+// Yields the keys 0..count while hinting at no size at all
+struct NoSizeHint(std::ops::Range<u64>);
+
+impl Iterator for NoSizeHint {
+    type Item = (u64, u64);
+
+    fn next(&mut self) -> Option<(u64, u64)> {
+        self.0.next().map(|key| (key, key))
+    }
+}
+
+#[test]
+fn from_iter_with_capacity_does_not_grow() {
+    let table: SlickHash<u64, u64> = SlickHash::from_iter_with_capacity(NoSizeHint(0..1000), 1000);
+    assert_eq!(table.capacity(), 1000);
+    assert_eq!(table.len(), 1000);
+    // Growth would have reset the counter
+    assert_eq!(table.insertions_since_compaction(), 1000);
+    for key in 0..1000 {
+        assert_eq!(table.get(&key), Some(&key));
+    }
+}
+
+#[test]
+fn get_mut_or_spill_counts_and_grows_like_inserts() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(1000);
+    let keys = keys_in_block(&table, 0, 60);
+    for &key in &keys {
+        *table.get_mut_or_spill(key, 0) += key;
+    }
+    assert!(!table.direct_spills.is_empty());
+    assert_eq!(table.insertions_since_compaction(), keys.len());
+
+    let mut small: SlickHash<u64, u64> = SlickHash::with_capacity(20);
+    for &key in &keys {
+        *small.get_mut_or_spill(key, 0) += key;
+        assert!(small.len() <= small.capacity());
+    }
+    for &key in &keys {
+        assert_eq!(table.get(&key), Some(&key));
+        assert_eq!(small.get(&key), Some(&key));
+    }
+}
+
+#[test]
+fn get_mut_or_spill_places_keys_of_nearly_full_blocks_into_the_backyard() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(1000);
+    let keys = keys_in_block(&table, 0, 20);
+    for &key in &keys[..19] {
+        let _ = table.try_insert((key, key));
+    }
+    assert_eq!(table.block_range(0).len(), 19);
+    *table.get_mut_or_spill(keys[19], 0) += 5;
+    assert_eq!(table.block_range(0).len(), 19);
+    assert!(table.backyard.contains_key(&keys[19]));
+    assert_eq!(table.get(&keys[19]), Some(&5));
+    table.assert_routing_consistency();
+}
+
+#[test]
+fn swap_remove_keeps_entries_in_their_blocks() {
+    for refill in [false, true] {
+        let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(100);
+        table.set_refill_gapless_left_on_remove(refill);
+        // Block 3 overflows and shifts block 4 to the right
+        let keys: Vec<u64> = keys_in_block(&table, 3, 12).into_iter()
+            .chain(keys_in_block(&table, 4, 6))
+            .chain(keys_in_block(&table, 5, 4))
+            .collect();
+        for &key in &keys {
+            let _ = table.try_insert((key, key));
+        }
+        assert!(table.meta_data[4].offset > 0);
+        let first_of_block_4 = table.main_table[table.block_start(4)].0;
+        assert_eq!(table.remove(&first_of_block_4), Some(first_of_block_4));
+        for block_index in 0..table.number_of_blocks {
+            for (key, _) in &table.main_table[table.block_range(block_index)] {
+                assert_eq!(table.hash_block_index(key), block_index, "refill {refill}");
+            }
+        }
+        for key in keys.iter().filter(|&&key| key != first_of_block_4) {
+            assert_eq!(table.get(key), Some(key), "refill {refill}");
+        }
+        table.assert_routing_consistency();
+    }
+}
+
+#[test]
+fn auto_tune_spills_less_than_tiny_blocks() {
+    let sample = random_keys(476, 5000);
+    let params = SlickHash::<u64, u64>::auto_tune(&sample);
+    assert!((4..=64).contains(&params.block_size), "block size {}", params.block_size);
+    let spill = |params: SlickParams| {
+        let capacity = sample.len().div_ceil(params.block_size) * params.block_size;
+        let mut table: SlickHash<u64, u64> = SlickHash::with_params(capacity, params);
+        for &key in &sample {
+            let _ = table.try_insert((key, key));
+        }
+        table.backyard.len()
+    };
+    let tiny = SlickParams { block_size: 2, max_slick_size: 4, max_offset: 2, max_threshold: 2, ..SlickParams::default() };
+    assert!(spill(params) < spill(tiny));
+}
+
+#[test]
+fn default_key_is_a_key_like_any_other() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(100);
+    // Every slot starts out as a default pair, yet no gap slot is taken for the key 0
+    assert!(!table.contains(&0));
+    assert_eq!(table.get(&0), None);
+    assert_eq!(table.remove(&0), None);
+    assert_eq!(table.len(), 0);
+
+    // The key 0 in an overflowing block, which reaches into the former gap slots of its right
+    // neighbour
+    let keys = keys_in_block(&table, table.hash_block_index(&0), 13);
+    assert!(keys.contains(&0));
+    let mut reference: HashMap<u64, u64> = HashMap::new();
+    for &key in &keys {
+        let _ = table.try_insert((key, key + 7));
+        reference.entry(key).or_insert(key + 7);
+    }
+    assert!(table.contains(&0));
+    assert_eq!(table.get(&0), Some(&7));
+    assert_eq!(table.remove(&0), Some(7));
+    reference.remove(&0);
+    assert!(!table.contains(&0));
+    assert_eq!(table.get(&0), None);
+    assert_eq!(table.remove(&0), None);
+    assert_eq!(table.len(), reference.len());
+    for (key, value) in &reference {
+        assert_eq!(table.get(key), Some(value));
+    }
+}
+
+#[test]
+fn backyard_soft_limit_grows_once_while_the_backyard_stays_full() {
+    // Small keys all hash into block 0, however large the table grows
+    let mut table: SlickHash<u64, u64, BuildIdentity> = SlickHash::with_capacity_and_hasher(1000, BuildIdentity::default());
+    table.set_backyard_soft_limit(Some(5));
+    for key in 0..100 {
+        let _ = table.try_insert((key, key));
+    }
+    assert_eq!(table.capacity(), 2000);
+    assert!(table.backyard.len() > 5);
+    for key in 0..100 {
+        assert_eq!(table.get(&key), Some(&key));
+    }
+}
+
+#[test]
+fn count_bumped_for_predicts_soft_limit_growth() {
+    for limit in [None, Some(0)] {
+        let mut table: SlickHash<u64, u64, BuildIdentity> = SlickHash::with_capacity_and_hasher(1000, BuildIdentity::default());
+        // Fills block 0 up to the first insertion that bumps
+        let mut key = 0;
+        while table.count_bumped_for(&key) == 0 {
+            let _ = table.try_insert((key, key));
+            key += 1;
+        }
+        assert!(table.backyard.is_empty());
+        table.set_backyard_soft_limit(limit);
+        let predicted = table.count_bumped_for(&key);
+        let _ = table.try_insert((key, key));
+        if limit.is_some() {
+            assert_eq!((predicted, table.capacity()), (0, 2000));
+        } else {
+            assert!(predicted > 0);
+            assert_eq!(table.capacity(), 1000);
+            assert_eq!(predicted, table.backyard.iter().filter(|&(&backyard_key, _)| backyard_key != key).count());
+        }
+    }
+}
+
+#[test]
+fn hash_mappings_match_the_internal_ones() {
+    let hashes: Vec<u64> = random_keys(483, 1000).into_iter().chain([0, 1, u64::MAX / 2, u64::MAX - 1, u64::MAX]).collect();
+    for table in [SlickHash::<u64, u64>::with_capacity(1000), SlickHash::with_capacity_pow2(1000), SlickHash::with_capacity(30)] {
+        for &hash in &hashes {
+            let hashed = table.hashed_from(hash);
+            assert_eq!(table.block_for_hash(hash), hashed.block_index);
+            assert_eq!(table.threshold_for_hash(hash), hashed.threshold);
+            assert!(hashed.block_index < table.number_of_blocks());
+            assert!(hashed.threshold < table.max_threshold);
+        }
+        assert_eq!(table.block_for_hash(0), 0);
+        assert_eq!(table.block_for_hash(u64::MAX), table.number_of_blocks() - 1);
+        for key in 0..100u64 {
+            let hash = table.hash_builder.hash_one(key);
+            assert_eq!(table.block_for_hash(hash), table.hash_key(&key).block_index);
+        }
+    }
+}
+
+#[test]
+fn entry_or_insert_hashes_the_key_once() {
+    let mut table: SlickHash<CountedKey, u64> = SlickHash::with_capacity(1000);
+    for key in 0..200 {
+        assert_eq!(hashes_during(|| *table.entry(CountedKey(key)).or_insert(key) += 1), 1);
+    }
+    for key in 0..200 {
+        assert_eq!(hashes_during(|| *table.entry(CountedKey(key)).or_insert(0) += 1), 1);
+        assert_eq!(table.get(&CountedKey(key)), Some(&(key + 2)));
+    }
+}
+
+#[test]
+fn boundary_hashes_map_into_the_last_block() {
+    for capacity in [1000, 1280, 30] {
+        let mut table: SlickHash<u64, u64, BuildIdentity> = SlickHash::with_capacity_and_hasher(capacity, BuildIdentity::default());
+        let last = table.number_of_blocks - 1;
+        // The largest hashes and one right at the start of the last block
+        let blocks = table.number_of_blocks as u64;
+        let boundary_keys = [u64::MAX, u64::MAX - 1, (u64::MAX / blocks + 1) * (blocks - 1)];
+        for key in boundary_keys {
+            assert_eq!(table.hash_key(&key).block_index, last, "capacity {capacity}");
+            assert!(table.try_insert((key, 1)).is_inserted());
+            assert_eq!(table.get(&key), Some(&1));
+        }
+        for key in boundary_keys {
+            assert_eq!(table.remove(&key), Some(1));
+            assert_eq!(table.get(&key), None);
+        }
+    }
+}
+
+#[test]
+fn extend_grows_at_most_once_for_an_exact_size_hint() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(100);
+    let pairs: Vec<(u64, u64)> = (0..1000).map(|key| (key, key)).collect();
+    table.extend(pairs);
+    assert_eq!(table.capacity(), 1000);
+    assert_eq!(table.len(), 1000);
+    // Growth while inserting would have reset the counter
+    assert_eq!(table.insertions_since_compaction(), 1000);
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn remove_random_empties_the_table() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(100);
+    let keys = random_keys(487, 300);
+    for &key in &keys[..250] {
+        let _ = table.try_insert((key, key));
+    }
+    for &key in &keys[250..] {
+        let _ = table.insert_into_backyard_pub(key, key);
+    }
+    let mut random = Random(487);
+    let mut removed = Vec::new();
+    while let Some((key, value)) = table.remove_random(&mut random) {
+        assert_eq!(key, value);
+        removed.push(key);
+    }
+    assert!(table.is_empty());
+    removed.sort_unstable();
+    let mut inserted = keys;
+    inserted.sort_unstable();
+    assert_eq!(removed, inserted);
+}
+
+fn new_through_the_trait<T: HashTableBase<u64, u64>>() -> T {
+    let mut table = T::new();
+    assert!(table.try_insert((1, 1)).is_inserted());
+    table
+}
+
+#[test]
+fn new_uses_the_default_capacity() {
+    let table: SlickHash<u64, u64> = new_through_the_trait();
+    assert_eq!(table.capacity(), <SlickHash<u64, u64> as HashTableBase<u64, u64>>::DEFAULT_CAPACITY);
+    assert_eq!(<VecTable as HashTableBase<u64, u64>>::DEFAULT_CAPACITY, 0);
+    assert_eq!(new_through_the_trait::<VecTable>().0, [(1, 1)]);
+    assert_eq!(new_through_the_trait::<HashMap<u64, u64>>().len(), 1);
+}
+
+#[test]
+fn validate_reports_every_violation() {
+    assert_eq!(SlickParams::default().validate(), Ok(()));
+    let params = params_with(|params| {
+        params.max_slick_size = params.block_size - 1;
+        params.max_offset = 0;
+        params.max_threshold = 0;
+    });
+    assert_eq!(params.validate(), Err(vec![
+        ParamError::MaxSlickSizeBelowBlockSize,
+        ParamError::MaxOffsetOutOfRange,
+        ParamError::ZeroMaxThreshold,
+    ]));
+    assert_eq!(param_error(100, params), Some(ParamError::MaxSlickSizeBelowBlockSize));
+}
+
+#[test]
+fn table_stays_usable_after_a_panic_mid_drain() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(100);
+    for key in 0..150 {
+        let _ = table.try_insert((key, key));
+    }
+    for key in 1000..1010 {
+        let _ = table.insert_into_backyard_pub(key, key);
+    }
+    let capacity = table.capacity();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        for (index, _) in table.drain().enumerate() {
+            if index == 20 {
+                panic!("consumer fails mid-drain");
+            }
+        }
+    }));
+    assert!(result.is_err());
+    assert!(table.is_empty());
+    assert_eq!(table.capacity(), capacity);
+    assert!(table.direct_spills.is_empty());
+    table.assert_routing_consistency();
+    for key in 0..150 {
+        assert_eq!(table.get(&key), None);
+        assert!(table.try_insert((key, key + 1)).is_inserted());
+    }
+    for key in 0..150 {
+        assert_eq!(table.get(&key), Some(&(key + 1)));
+    }
+    assert_eq!(table.len(), 150);
+}
+
+#[test]
+fn flooding_keys_make_the_table_reseed() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(1024);
+    table.set_reseed_on_flood(Some(BuildFastMix::reseeded));
+    let keys = keys_in_block(&table, 0, 300);
+    for &key in &keys {
+        assert!(table.try_insert((key, key)).is_inserted());
+    }
+    assert!(table.reseed_count() > 0);
+    assert_eq!(table.len(), keys.len());
+    for &key in &keys {
+        assert_eq!(table.get(&key), Some(&key));
+    }
+    table.assert_routing_consistency();
+}
+
+#[test]
+fn benign_keys_never_make_the_table_reseed() {
+    let mut table: SlickHash<u64, u64> = SlickHash::new();
+    table.set_reseed_on_flood(Some(BuildFastMix::reseeded));
+    for key in random_keys(7, 20_000) {
+        assert!(table.try_insert((key, key)).is_inserted());
+    }
+    assert_eq!(table.reseed_count(), 0);
+}
+
+#[test]
+fn mem_usage_counts_the_keys_of_direct_spills() {
+    let mut table: SlickHash<u64, u64> = SlickHash::with_capacity(100);
+    table.backyard.reserve(16);
+    let before = table.mem_usage();
+    for key in 0..10 {
+        let _ = table.insert_into_backyard_pub(key, key);
+    }
+    assert_eq!(
+        table.mem_usage() - before,
+        estimated_hashmap_bytes::<u64, ()>(table.direct_spills.capacity())
+    );
+}
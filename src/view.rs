@@ -0,0 +1,74 @@
+use crate::SlickHash;
+use crate::BuildFastMix;
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+
+/// Read-only facade over a `SlickHash` with the lookup and iteration methods of a std
+/// `HashMap`, for code written against those. Created by `SlickHash::view`.
+pub struct SlickHashView<'t, Key, Value, S = BuildFastMix> {
+    table: &'t SlickHash<Key, Value, S>,
+}
+
+impl<Key, Value, S> Clone for SlickHashView<'_, Key, Value, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Key, Value, S> Copy for SlickHashView<'_, Key, Value, S> {}
+
+impl<Key, Value, S> SlickHash<Key, Value, S>
+where
+    Key: Clone + Eq + PartialEq + Hash + Default,
+    Value: Clone + Default,
+    S: BuildHasher + Clone,
+{
+    pub fn view(&self) -> SlickHashView<'_, Key, Value, S> {
+        SlickHashView { table: self }
+    }
+}
+
+impl<'t, Key, Value, S> SlickHashView<'t, Key, Value, S>
+where
+    Key: Clone + Eq + PartialEq + Hash + Default,
+    Value: Clone + Default,
+    S: BuildHasher + Clone,
+{
+    pub fn get<Q>(&self, key: &Q) -> Option<&'t Value>
+    where
+        Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let table = self.table;
+        table.get_hashed(key, table.hash_key(key))
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// All entries in arbitrary order: block by block, followed by the backyard
+    pub fn iter(&self) -> impl Iterator<Item = (&'t Key, &'t Value)> + 't {
+        self.table.entries()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &'t Key> + 't {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &'t Value> + 't {
+        self.iter().map(|(_, value)| value)
+    }
+}